@@ -1,8 +1,12 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use fast_ntt::{
-    ntt::{forward, working_modulus, Constants},
-    numbers::BigInt,
-    polynomial::{fast_mul, mul_brute, Polynomial, PolynomialFieldElement, PolynomialTrait},
+    ntt::{forward, forward_batch, working_modulus, Constants, NttPlan},
+    numbers::{BigInt, Fp998244353, Mod64},
+    polynomial::{
+        fast_mul, fast_mul_with_plan, mul_brute, mul_unbalanced, MulThresholds, Polynomial,
+        PolynomialFieldElement,
+    },
+    rns::exact_mul,
 };
 use itertools::Itertools;
 
@@ -15,6 +19,13 @@ fn bench_mul<T: PolynomialFieldElement>(x: usize, y: usize, c: &Constants<T>) {
     let _ = fast_mul(a, b, c);
 }
 
+fn bench_mul_plan<T: PolynomialFieldElement>(x: usize, y: usize, plan: &NttPlan<T>) {
+    let ONE = T::from(1);
+    let a = Polynomial::new(vec![0; x].iter().map(|_| ONE).collect_vec());
+    let b = Polynomial::new(vec![0; y].iter().map(|_| ONE).collect_vec());
+    let _ = fast_mul_with_plan(a, b, plan);
+}
+
 fn bench_mul_brute<T: PolynomialFieldElement>(x: usize, y: usize) {
     let ONE = T::from(1);
     let a = Polynomial::new(vec![0; x].iter().map(|_| ONE).collect_vec());
@@ -22,12 +33,38 @@ fn bench_mul_brute<T: PolynomialFieldElement>(x: usize, y: usize) {
     let _ = mul_brute(a, b);
 }
 
+fn bench_exact_mul(x: usize, y: usize) {
+    let ONE = BigInt::from(1);
+    let a = Polynomial::new(vec![0; x].iter().map(|_| ONE).collect_vec());
+    let b = Polynomial::new(vec![0; y].iter().map(|_| ONE).collect_vec());
+    let _ = exact_mul(&a, &b);
+}
+
+fn bench_mul_thresholds(x: usize, y: usize, t: &MulThresholds, c: &Constants<Fp998244353>) {
+    let ONE = Fp998244353::from(1);
+    let a = Polynomial::new(vec![ONE; x]);
+    let b = Polynomial::new(vec![ONE; y]);
+    let _ = t.mul(&a, &b, c);
+}
+
+fn bench_mul_unbalanced(x: usize, y: usize, c: &Constants<Fp998244353>) {
+    let ONE = Fp998244353::from(1);
+    let a = Polynomial::new(vec![ONE; x]);
+    let b = Polynomial::new(vec![ONE; y]);
+    let _ = mul_unbalanced(&a, &b, c);
+}
+
 fn bench_forward<T: PolynomialFieldElement>(n: usize, c: &Constants<T>) {
     let ONE = T::from(1);
     let a = Polynomial::new(vec![0; n].iter().map(|_| ONE).collect_vec());
     let _ = forward(a.coef, c);
 }
 
+fn bench_forward_batch(n: usize, rows: usize, c: &Constants<Fp998244353>) {
+    let mut matrix = vec![Fp998244353::from(1); n * rows];
+    forward_batch(&mut matrix, n, c);
+}
+
 fn criterion_forward(c: &mut Criterion) {
     let mut group = c.benchmark_group("Number-Theoretic Transform Benchmarks");
     (6..deg).for_each(|n| {
@@ -36,6 +73,13 @@ fn criterion_forward(c: &mut Criterion) {
         group.bench_with_input(id, &n, |b, n| {
             b.iter(|| bench_forward(black_box(1 << n), black_box(&c)))
         });
+
+        // 64 rows sharing one twiddle table
+        let id = BenchmarkId::new("NTT-Batch", 1 << n);
+        let c = Constants::<Fp998244353>::for_size(1 << n);
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_forward_batch(black_box(1 << n), black_box(64), black_box(&c)))
+        });
     });
 }
 
@@ -44,13 +88,37 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     (6..deg).for_each(|n| {
         let id = BenchmarkId::new("NTT-Based", 1 << n);
-        let N = BigInt::from((2 * n).next_power_of_two());
+        let N = BigInt::from(1 << (n + 1));
         let M = N << 1 + 1;
         let c = working_modulus(N, M);
         group.bench_with_input(id, &n, |b, n| {
             b.iter(|| bench_mul(black_box(1 << n), black_box(1 << n), black_box(&c)))
         });
 
+        let id = BenchmarkId::new("NTT-Plan", 1 << n);
+        let plan = NttPlan::new(&c, 1 << (n + 1));
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_mul_plan(black_box(1 << n), black_box(1 << n), black_box(&plan)))
+        });
+
+        let id = BenchmarkId::new("NTT-Mod64", 1 << n);
+        let c = working_modulus(Mod64::from(1_u64 << (n + 1)), Mod64::from(1_u64 << 20));
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_mul(black_box(1 << n), black_box(1 << n), black_box(&c)))
+        });
+
+        let id = BenchmarkId::new("RNS", 1 << n);
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_exact_mul(black_box(1 << n), black_box(1 << n)))
+        });
+
+        // 128 terms against 2^n, one small transform per block
+        let id = BenchmarkId::new("Overlap-Add", 1 << n);
+        let c = Constants::<Fp998244353>::for_size(1 << 8);
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_mul_unbalanced(black_box(128), black_box(1 << n), black_box(&c)))
+        });
+
         let id = BenchmarkId::new("Brute-Force", 1 << n);
         group.bench_with_input(id, &n, |b, n| {
             b.iter(|| bench_mul_brute::<BigInt>(black_box(1 << n), black_box(1 << n)))
@@ -58,9 +126,51 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 }
 
+// forces each algorithm in turn to locate the `MulThresholds` crossovers,
+// for balanced operands and for a short operand against one 16 times longer
+fn criterion_crossover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Multiplication Crossover");
+    let c = Constants::<Fp998244353>::for_size(1 << 16);
+    let algorithms = [
+        (
+            "Schoolbook",
+            MulThresholds {
+                karatsuba: usize::MAX,
+                ntt: usize::MAX,
+            },
+        ),
+        (
+            "Karatsuba",
+            MulThresholds {
+                karatsuba: 16,
+                ntt: usize::MAX,
+            },
+        ),
+        (
+            "NTT",
+            MulThresholds {
+                karatsuba: 0,
+                ntt: 0,
+            },
+        ),
+    ];
+    (3..11).for_each(|n| {
+        algorithms.iter().for_each(|(name, t)| {
+            let id = BenchmarkId::new(*name, 1 << n);
+            group.bench_with_input(id, &n, |b, n| {
+                b.iter(|| bench_mul_thresholds(black_box(1 << n), black_box(1 << n), t, &c))
+            });
+            let id = BenchmarkId::new(format!("{name}-Unbalanced"), 1 << n);
+            group.bench_with_input(id, &n, |b, n| {
+                b.iter(|| bench_mul_thresholds(black_box(1 << n), black_box(16 << n), t, &c))
+            });
+        });
+    });
+}
+
 criterion_group! {
   name = benches;
   config = Criterion::default().sample_size(10);
-  targets = criterion_forward, criterion_benchmark
+  targets = criterion_forward, criterion_benchmark, criterion_crossover
 }
 criterion_main!(benches);
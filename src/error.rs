@@ -0,0 +1,64 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NttError {
+    /// A power-of-two transform was asked for a length that is not one.
+    NonPowerOfTwoLength(usize),
+    /// The input does not have the length a plan or ring was built for.
+    LengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    ModulusNotPrime,
+    /// The modulus is outside what the element type can represent.
+    InvalidModulus,
+    /// There is no primitive root of unity of this order.
+    NoRootOfUnity(usize),
+    /// `w` does not have power-of-two order.
+    RootNotPowerOfTwoOrder,
+    EvenModulus,
+    ZeroPolynomial,
+    Overflow,
+    /// The element or polynomial has no inverse modulo `N`.
+    NotInvertible,
+    /// The constant term is outside the domain of a power-series function,
+    /// such as `log` of a series not starting with 1.
+    InvalidConstantTerm,
+    /// Integration would divide a nonzero term by this degree, which is a
+    /// multiple of the characteristic.
+    CharacteristicTooSmall(usize),
+    /// The operands were built with different `Constants`.
+    ConstantsMismatch,
+    /// The polynomial is not a product of distinct irreducible factors of
+    /// this degree.
+    NotEqualDegree(usize),
+}
+
+impl Display for NttError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NttError::NonPowerOfTwoLength(n) => write!(f, "length {} is not a power of two", n),
+            NttError::LengthMismatch { expected, found } => {
+                write!(f, "expected length {}, found {}", expected, found)
+            }
+            NttError::ModulusNotPrime => write!(f, "modulus is not prime"),
+            NttError::InvalidModulus => write!(f, "modulus is not supported by this type"),
+            NttError::NoRootOfUnity(n) => write!(f, "no root of unity of order {}", n),
+            NttError::RootNotPowerOfTwoOrder => write!(f, "`w` does not have power-of-two order"),
+            NttError::EvenModulus => write!(f, "modulus must be odd"),
+            NttError::ZeroPolynomial => write!(f, "zero polynomial"),
+            NttError::Overflow => write!(f, "value exceeds the size limits of the type"),
+            NttError::NotInvertible => write!(f, "value is not invertible"),
+            NttError::InvalidConstantTerm => write!(f, "constant term outside the domain"),
+            NttError::CharacteristicTooSmall(d) => {
+                write!(f, "cannot divide by degree {} in this characteristic", d)
+            }
+            NttError::ConstantsMismatch => write!(f, "operands have different constants"),
+            NttError::NotEqualDegree(d) => {
+                write!(f, "not a product of distinct factors of degree {}", d)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NttError {}
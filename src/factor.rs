@@ -0,0 +1,593 @@
+use rand::Rng;
+
+use crate::{
+    error::NttError,
+    ntt::Constants,
+    numbers::BigInt,
+    polynomial::{
+        add_scaled, lift, mul_stripped, pow_mod_stripped, remainder_gcd, strip, try_div_rem,
+        unstrip, Polynomial, PolynomialFieldElement,
+    },
+};
+
+// Every helper works on stripped, lifted coefficient vectors, highest degree
+// first, with the prime field `Z_N` given by `c`.
+
+fn monic<T: PolynomialFieldElement>(v: Vec<T>) -> Vec<T> {
+    let lead_inv = v[0].invert();
+    v.into_iter().map(|x| x * lead_inv).collect()
+}
+
+fn is_one<T: PolynomialFieldElement>(v: &[T]) -> bool {
+    v.len() == 1
+}
+
+fn gcd_monic<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let (g, _) = remainder_gcd(a.to_vec(), b.to_vec(), c)?;
+    Ok(monic(g))
+}
+
+fn div_rem<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<(Vec<T>, Vec<T>), NttError> {
+    let (q, r) = try_div_rem(Polynomial::new(a.to_vec()), Polynomial::new(b.to_vec()), c)?;
+    Ok((strip(q.coef), strip(r.coef)))
+}
+
+fn derivative<T: PolynomialFieldElement>(f: &[T], c: &Constants<T>) -> Vec<T> {
+    let n = f.len();
+    strip(
+        f[..n - 1]
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| x * c.reduce(T::from(n - 1 - i)))
+            .collect(),
+    )
+}
+
+// `x mod f`
+fn x_mod<T: PolynomialFieldElement>(f: &[T], c: &Constants<T>) -> Result<Vec<T>, NttError> {
+    let x = vec![c.reduce(T::from(1)), c.reduce(T::from(0))];
+    Ok(div_rem(&x, f, c)?.1)
+}
+
+// `g(x)` for `f = g(x^p)`, using that `a^p = a` for every `a` in `Z_p`
+fn pth_root<T: PolynomialFieldElement>(f: &[T], p: usize) -> Vec<T> {
+    f.iter().step_by(p).cloned().collect()
+}
+
+// random element of `Z_N` folded from 256 random bits in 32-bit steps, so
+// that no modulus overflows `T::from`
+fn random_element<T: PolynomialFieldElement>(c: &Constants<T>) -> T {
+    let ZERO = c.reduce(T::from(0));
+    let SHIFT = c.reduce(T::from(1_u64 << 32));
+    let mut rng = rand::thread_rng();
+    (0..8).fold(ZERO, |acc, _| {
+        acc * SHIFT + c.reduce(T::from(rng.gen::<u32>()))
+    })
+}
+
+// random polynomial with fewer terms than `f`
+fn random_below<T: PolynomialFieldElement>(f: &[T], c: &Constants<T>) -> Vec<T> {
+    strip((1..f.len()).map(|_| random_element(c)).collect())
+}
+
+// Yun's algorithm, with a `p`-th root whenever the derivative vanishes
+fn square_free_parts<T: PolynomialFieldElement>(
+    f: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<(Vec<T>, usize)>, NttError> {
+    let mut res = vec![];
+    let df = derivative(f, c);
+    let mut rest = gcd_monic(f, &df, c)?;
+    let mut w = div_rem(f, &rest, c)?.0;
+    let mut i = 1;
+    while !is_one(&w) {
+        let y = gcd_monic(&w, &rest, c)?;
+        let fac = div_rem(&w, &y, c)?.0;
+        if !is_one(&fac) {
+            res.push((monic(fac), i));
+        }
+        rest = div_rem(&rest, &y, c)?.0;
+        w = y;
+        i += 1;
+    }
+    // what is left only has exponents divisible by `p`
+    if !is_one(&rest) {
+        let p = c.N.to_bigint().to_u64()? as usize;
+        square_free_parts(&pth_root(&rest, p), c)?
+            .into_iter()
+            .for_each(|(g, k)| res.push((g, k * p)));
+    }
+    Ok(res)
+}
+
+// products of the irreducible factors of each degree of square-free monic
+// `f`, from the gcds of `f` with `x^(p^i) - x`
+fn distinct_degree_parts<T: PolynomialFieldElement>(
+    f: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<(Vec<T>, usize)>, NttError> {
+    let p = c.N.to_bigint();
+    let mut res = vec![];
+    let mut rest = f.to_vec();
+    let mut h = x_mod(&rest, c)?;
+    let mut i = 1;
+    while rest.len() > 2 * i {
+        h = pow_mod_stripped(&h, p, &rest, c)?;
+        let x = x_mod(&rest, c)?;
+        let g = gcd_monic(&rest, &add_scaled(&h, &x, true), c)?;
+        if !is_one(&g) {
+            rest = div_rem(&rest, &g, c)?.0;
+            h = div_rem(&h, &rest, c)?.1;
+            res.push((g, i));
+        }
+        i += 1;
+    }
+    if !is_one(&rest) {
+        let d = rest.len() - 1;
+        res.push((rest, d));
+    }
+    Ok(res)
+}
+
+// random splits `equal_degree_parts` and `split_linear` try before giving
+// up; each one succeeds with probability at least 1/2 when `f` has the
+// expected shape
+const SPLIT_ATTEMPTS: usize = 64;
+
+// Cantor-Zassenhaus splitting of monic `f` whose irreducible factors all
+// have degree `d`
+fn equal_degree_parts<T: PolynomialFieldElement>(
+    f: &[T],
+    d: usize,
+    c: &Constants<T>,
+) -> Result<Vec<Vec<T>>, NttError> {
+    if f.len() - 1 == d {
+        return Ok(vec![f.to_vec()]);
+    }
+    let p = c.N.to_bigint();
+    let ONE = c.reduce(T::from(1));
+    for _ in 0..SPLIT_ATTEMPTS {
+        let a = random_below(f, c);
+        if a.len() < 2 {
+            continue;
+        }
+        // a^(1 + p + ... + p^(d-1)) is the norm down to `Z_p` of each
+        // residue, and for p = 2 the trace a + a^2 + ... + a^(2^(d-1))
+        // splits instead
+        let mut frob = a.clone();
+        let mut acc = a.clone();
+        (1..d).try_for_each(|_| {
+            frob = pow_mod_stripped(&frob, p, f, c)?;
+            acc = match p.is_even() {
+                true => add_scaled(&acc, &frob, false),
+                false => div_rem(&mul_stripped(&acc, &frob, c)?, f, c)?.1,
+            };
+            Ok::<(), NttError>(())
+        })?;
+        let b = match p.is_even() {
+            true => acc,
+            false => {
+                let half = (p - BigInt::from(1)) >> 1;
+                add_scaled(&pow_mod_stripped(&acc, half, f, c)?, &[ONE], true)
+            }
+        };
+        let g = match b.is_empty() {
+            true => continue,
+            false => gcd_monic(f, &b, c)?,
+        };
+        if !is_one(&g) && g.len() < f.len() {
+            let mut res = equal_degree_parts(&g, d, c)?;
+            res.extend(equal_degree_parts(&div_rem(f, &g, c)?.0, d, c)?);
+            return Ok(res);
+        }
+    }
+    Err(NttError::NotEqualDegree(d))
+}
+
+// lifted, stripped `f`, rejecting the zero polynomial
+fn nonzero<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let f = lift(f, c);
+    if f.is_empty() {
+        return Err(NttError::ZeroPolynomial);
+    }
+    Ok(f)
+}
+
+fn to_pairs<T: PolynomialFieldElement>(
+    parts: Vec<(Vec<T>, usize)>,
+    c: &Constants<T>,
+) -> Vec<(Polynomial<T>, usize)> {
+    parts.into_iter().map(|(g, k)| (unstrip(g, c), k)).collect()
+}
+
+/// Square-free factorization of `f` over the prime field `Z_N`: monic,
+/// square-free and pairwise coprime `(g, k)` with `f = lc(f) * prod g^k`.
+pub fn square_free<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Vec<(Polynomial<T>, usize)> {
+    try_square_free(f, c).unwrap()
+}
+
+pub fn try_square_free<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Vec<(Polynomial<T>, usize)>, NttError> {
+    let f = monic(nonzero(f, c)?);
+    if is_one(&f) {
+        return Ok(vec![]);
+    }
+    Ok(to_pairs(square_free_parts(&f, c)?, c))
+}
+
+/// Distinct-degree factorization of square-free `f`: `(g, d)` where `g` is
+/// the monic product of all irreducible factors of `f` of degree `d`.
+pub fn distinct_degree<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Vec<(Polynomial<T>, usize)> {
+    try_distinct_degree(f, c).unwrap()
+}
+
+pub fn try_distinct_degree<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Vec<(Polynomial<T>, usize)>, NttError> {
+    let f = monic(nonzero(f, c)?);
+    if is_one(&f) {
+        return Ok(vec![]);
+    }
+    Ok(to_pairs(distinct_degree_parts(&f, c)?, c))
+}
+
+/// Cantor-Zassenhaus splitting of `f`, a product of distinct irreducible
+/// factors of degree `d`, into those monic factors.
+pub fn equal_degree<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    d: usize,
+    c: &Constants<T>,
+) -> Vec<Polynomial<T>> {
+    try_equal_degree(f, d, c).unwrap()
+}
+
+pub fn try_equal_degree<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    d: usize,
+    c: &Constants<T>,
+) -> Result<Vec<Polynomial<T>>, NttError> {
+    let f = monic(nonzero(f, c)?);
+    if d == 0 || (f.len() - 1) % d != 0 {
+        return Err(NttError::LengthMismatch {
+            expected: d,
+            found: f.len() - 1,
+        });
+    }
+    if is_one(&f) {
+        return Ok(vec![]);
+    }
+    Ok(equal_degree_parts(&f, d, c)?
+        .into_iter()
+        .map(|g| unstrip(g, c))
+        .collect())
+}
+
+/// Factorization of `f` over the prime field `Z_N` into monic irreducible
+/// factors with their multiplicities, by degree and then coefficients; the
+/// leading coefficient of `f` is dropped. `c.w` must support products of
+/// twice the length of `f`.
+pub fn factor<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Vec<(Polynomial<T>, usize)> {
+    try_factor(f, c).unwrap()
+}
+
+pub fn try_factor<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Vec<(Polynomial<T>, usize)>, NttError> {
+    let f = monic(nonzero(f, c)?);
+    if is_one(&f) {
+        return Ok(vec![]);
+    }
+    let mut res = vec![];
+    for (g, k) in square_free_parts(&f, c)? {
+        for (h, d) in distinct_degree_parts(&g, c)? {
+            equal_degree_parts(&h, d, c)?
+                .into_iter()
+                .for_each(|e| res.push((e, k)));
+        }
+    }
+    res.sort_by(|(a, _), (b, _)| {
+        a.len()
+            .cmp(&b.len())
+            .then(a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    Ok(to_pairs(res, c))
+}
+
+/// Rabin's irreducibility test over the prime field `Z_N`: `f` of degree
+/// `n > 0` is irreducible iff `x^(p^n) = x mod f` and `x^(p^(n/q)) - x` is
+/// coprime to `f` for every prime `q` dividing `n`.
+pub fn is_irreducible<T: PolynomialFieldElement>(f: &Polynomial<T>, c: &Constants<T>) -> bool {
+    try_is_irreducible(f, c).unwrap()
+}
+
+pub fn try_is_irreducible<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<bool, NttError> {
+    let f = monic(nonzero(f, c)?);
+    let n = f.len() - 1;
+    if n <= 1 {
+        return Ok(n == 1);
+    }
+    let p = c.N.to_bigint();
+    let x = x_mod(&f, c)?;
+    let divisors: Vec<usize> = (2..=n)
+        .filter(|&q| n % q == 0 && (2..q).all(|r| q % r != 0))
+        .map(|q| n / q)
+        .collect();
+
+    // h = x^(p^i) mod f
+    let mut h = x.clone();
+    for i in 1..=n {
+        h = pow_mod_stripped(&h, p, &f, c)?;
+        if divisors.contains(&i) && !is_one(&gcd_monic(&f, &add_scaled(&h, &x, true), c)?) {
+            return Ok(false);
+        }
+    }
+    Ok(h == x)
+}
+
+// roots of monic `g`, a product of distinct linear factors, split by
+// gcds with `(x + a)^((p - 1) / 2) - 1` for random shifts `a`
+fn split_linear<T: PolynomialFieldElement>(g: &[T], c: &Constants<T>) -> Result<Vec<T>, NttError> {
+    match g.len() {
+        1 => return Ok(vec![]),
+        2 => return Ok(vec![-g[1]]),
+        _ => {}
+    }
+    let ONE = c.reduce(T::from(1));
+    let half = (c.N.to_bigint() - BigInt::from(1)) >> 1;
+    for _ in 0..SPLIT_ATTEMPTS {
+        let a = random_element(c);
+        let h = pow_mod_stripped(&[ONE, a], half, g, c)?;
+        let d = match add_scaled(&h, &[ONE], true) {
+            b if b.is_empty() => continue,
+            b => gcd_monic(g, &b, c)?,
+        };
+        if !is_one(&d) && d.len() < g.len() {
+            let mut res = split_linear(&d, c)?;
+            res.extend(split_linear(&div_rem(g, &d, c)?.0, c)?);
+            return Ok(res);
+        }
+    }
+    Err(NttError::NotEqualDegree(1))
+}
+
+/// Distinct roots of `f` in the prime field `Z_N`, in increasing order,
+/// from `gcd(f, x^p - x)` split with random shifts.
+pub fn roots<T: PolynomialFieldElement>(f: &Polynomial<T>, c: &Constants<T>) -> Vec<T> {
+    try_roots(f, c).unwrap()
+}
+
+pub fn try_roots<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let f = monic(nonzero(f, c)?);
+    let p = c.N.to_bigint();
+    // no odd-order shifts to split with, but only 0 and 1 to try
+    if p.is_even() {
+        let ZERO = c.reduce(T::from(0));
+        return Ok([ZERO, c.reduce(T::from(1))]
+            .into_iter()
+            .filter(|&x| f.iter().fold(ZERO, |acc, &a| acc * x + a).is_zero())
+            .collect());
+    }
+
+    let x = x_mod(&f, c)?;
+    let xp = pow_mod_stripped(&x, p, &f, c)?;
+    let g = gcd_monic(&f, &add_scaled(&xp, &x, true), c)?;
+    let mut res = split_linear(&g, c)?;
+    res.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::Invert;
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use super::{
+        distinct_degree, equal_degree, factor, is_irreducible, roots, square_free,
+        try_equal_degree, try_factor, try_roots,
+    };
+    use crate::{
+        error::NttError,
+        ntt::Constants,
+        numbers::{Fp998244353, Mod64},
+        polynomial::{mul_auto, Polynomial},
+    };
+
+    fn poly(v: &[u32]) -> Polynomial<Fp998244353> {
+        Polynomial::new(v.iter().map(|&x| Fp998244353::from(x)).collect())
+    }
+
+    fn product(
+        parts: &[(Polynomial<Fp998244353>, usize)],
+        c: &Constants<Fp998244353>,
+    ) -> Polynomial<Fp998244353> {
+        parts.iter().fold(poly(&[1]), |acc, (g, k)| {
+            (0..*k).fold(acc, |acc, _| mul_auto(&acc, g, c))
+        })
+    }
+
+    #[test]
+    fn test_factor() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        // 3 generates the multiplicative group, so x^2 - 3 has no roots
+        let q = poly(&[1, 0, 998244350]);
+        let lin = poly(&[1, 5]);
+        let cubic = poly(&[1, 0, 0, 2]);
+        assert!(is_irreducible(&q, &c));
+        assert!(is_irreducible(&lin, &c));
+        assert!(!is_irreducible(&mul_auto(&q, &lin, &c), &c));
+
+        let parts = vec![(lin.clone(), 3), (q.clone(), 2), (poly(&[1, 7]), 1)];
+        let f = product(&parts, &c) * Fp998244353::from(4);
+        let sf = square_free(&f, &c);
+        assert_eq!(product(&sf, &c).coef, product(&parts, &c).coef);
+        assert_eq!(sf.iter().map(|(_, k)| *k).collect_vec(), vec![1, 2, 3]);
+
+        let facs = factor(&f, &c);
+        assert_eq!(
+            facs.iter().map(|(g, k)| (g.coef.len(), *k)).collect_vec(),
+            vec![(2, 3), (2, 1), (3, 2)]
+        );
+        assert_eq!(product(&facs, &c).coef, product(&parts, &c).coef);
+        facs.iter()
+            .for_each(|(g, _)| assert!(is_irreducible(g, &c)));
+
+        // x^3 + 2 splits into irreducibles of total degree 3
+        let cubic_facs = factor(&cubic, &c);
+        assert_eq!(product(&cubic_facs, &c).coef, cubic.coef);
+        let degrees: usize = distinct_degree(&cubic, &c)
+            .iter()
+            .map(|(g, d)| (g.coef.len() - 1) / d * d)
+            .sum();
+        assert_eq!(degrees, 3);
+
+        let random = Polynomial::new(
+            (0..20)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>() % 998244353 + 1))
+                .collect(),
+        );
+        let lead_inv = random.coef[0].invert();
+        assert_eq!(
+            product(&factor(&random, &c), &c).coef,
+            (random.clone() * lead_inv).coef
+        );
+
+        let zero = poly(&[0, 0]);
+        assert_eq!(try_factor(&zero, &c).unwrap_err(), NttError::ZeroPolynomial);
+        assert!(factor(&poly(&[5]), &c).is_empty());
+    }
+
+    #[test]
+    fn test_factor_small_characteristic() {
+        // over Z_3, x^6 + 2x^3 + 1 = (x^3 + 1)^2 = (x + 1)^6
+        let c = Constants {
+            N: Mod64::from(3_u64),
+            w: Mod64::from(2_u64),
+        };
+        let f = Polynomial::new(
+            [1_u64, 0, 0, 2, 0, 0, 1]
+                .iter()
+                .map(|&x| Mod64::from(x))
+                .collect(),
+        );
+        let facs = factor(&f, &c);
+        assert_eq!(facs.len(), 1);
+        assert_eq!(facs[0].0.coef, vec![Mod64::new(1, 3), Mod64::new(1, 3)]);
+        assert_eq!(facs[0].1, 6);
+
+        // x^2 + 1 is irreducible over Z_3, x^2 + x + 1 = (x - 1)^2 is not
+        let g = Polynomial::new([1_u64, 0, 1].iter().map(|&x| Mod64::from(x)).collect());
+        let h = Polynomial::new([1_u64, 1, 1].iter().map(|&x| Mod64::from(x)).collect());
+        assert!(is_irreducible(&g, &c));
+        assert!(!is_irreducible(&h, &c));
+
+        // x^4 + x + 2 is irreducible over Z_3, so it has no quadratic factors
+        let quartic = Polynomial::new(
+            [1_u64, 0, 0, 1, 2]
+                .iter()
+                .map(|&x| Mod64::from(x))
+                .collect(),
+        );
+        assert!(is_irreducible(&quartic, &c));
+        let k = Polynomial::new([1_u64, 1, 2].iter().map(|&x| Mod64::from(x)).collect());
+        let split = equal_degree(&mul_auto(&g, &k, &c), 2, &c)
+            .iter()
+            .map(|f| f.coef.iter().map(|x| x.v).collect_vec())
+            .sorted()
+            .collect_vec();
+        assert_eq!(split, vec![vec![1, 0, 1], vec![1, 1, 2]]);
+        assert_eq!(
+            try_equal_degree(&quartic, 2, &c).unwrap_err(),
+            NttError::NotEqualDegree(2)
+        );
+    }
+
+    #[test]
+    fn test_roots() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        let mut expected: Vec<Fp998244353> = (0..40)
+            .map(|_| rand::thread_rng().gen::<u32>() % 998244353)
+            .sorted()
+            .dedup()
+            .map(Fp998244353::from)
+            .collect();
+        // a repeated root and an irreducible quadratic add no roots
+        let f = expected.iter().fold(poly(&[1, 0, 998244350]), |acc, &r| {
+            mul_auto(&acc, &Polynomial::new(vec![Fp998244353::from(1), -r]), &c)
+        });
+        let f = mul_auto(
+            &f,
+            &Polynomial::new(vec![Fp998244353::from(1), -expected[0]]),
+            &c,
+        );
+        assert_eq!(roots(&f, &c), expected);
+
+        expected.truncate(1);
+        assert_eq!(
+            roots(
+                &Polynomial::new(vec![Fp998244353::from(1), -expected[0]]),
+                &c
+            ),
+            expected
+        );
+        assert!(roots(&poly(&[3]), &c).is_empty());
+        assert_eq!(
+            try_roots(&poly(&[0]), &c).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+
+        // x^3 + x = x (x^2 + 1) over Z_2 has the roots 0 and 1
+        let c = Constants {
+            N: Mod64::from(2_u64),
+            w: Mod64::from(1_u64),
+        };
+        let f = Polynomial::new([1_u64, 0, 1, 0].iter().map(|&x| Mod64::from(x)).collect());
+        assert_eq!(roots(&f, &c), vec![Mod64::new(0, 2), Mod64::new(1, 2)]);
+
+        // (x - 3)(x - 5) over Z_101
+        let c = Constants {
+            N: Mod64::from(101_u64),
+            w: Mod64::from(1_u64),
+        };
+        let f = Polynomial::new([1_u64, 93, 15].iter().map(|&x| Mod64::from(x)).collect());
+        assert_eq!(roots(&f, &c), vec![Mod64::new(3, 101), Mod64::new(5, 101)]);
+
+        // Z_9 is not a field, and no random shift splits x^2 + 1 there
+        let c = Constants {
+            N: Mod64::from(9_u64),
+            w: Mod64::from(1_u64),
+        };
+        let f = Polynomial::new([1_u64, 0, 1].iter().map(|&x| Mod64::from(x)).collect());
+        assert_eq!(try_roots(&f, &c).unwrap_err(), NttError::NotEqualDegree(1));
+    }
+}
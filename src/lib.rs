@@ -1,4 +1,10 @@
+pub mod error;
+pub mod factor;
 pub mod ntt;
 pub mod numbers;
 pub mod polynomial;
 pub mod prime;
+pub mod recurrence;
+pub mod rns;
+pub mod series;
+pub mod subproduct;
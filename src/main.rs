@@ -1 +1,420 @@
-fn main() {}
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read},
+    process,
+};
+
+use fast_ntt::{
+    error::NttError,
+    ntt::{try_forward, try_inverse, try_working_modulus, Constants},
+    numbers::{Mod64, NttFieldElement},
+    polynomial::{try_diff, try_fast_mul, CoefficientOrder, Polynomial},
+};
+use itertools::Itertools;
+
+const USAGE: &str = "\
+usage: fast-ntt <command> [options] [FILE...]
+
+commands:
+    mul [A] [B]          product of two polynomials
+    ntt [FILE]           forward transform
+    intt [FILE]          inverse transform, needs --modulus and --root
+    diff [FILE]          derivative
+    modulus LEN BOUND    prime N >= BOUND with a root of unity of order LEN
+
+Polynomials are read from the files, or from stdin when none (or `-`) is
+given, one per line as decimal or 0x-prefixed hex coefficients, or as a JSON
+array (of arrays). Coefficients are listed highest degree first unless
+`--order asc` is given. Arithmetic uses word-sized residues, so moduli must
+be below 2^62.
+
+options:
+    --modulus N          work modulo the prime N instead of picking one
+    --root W             primitive root of unity to pair with --modulus
+    --format FMT         output as `dec` (default), `hex` or `json`
+    --order ORD          coefficient order of input and output polynomials,
+                         `desc` (default) or `asc`";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Dec,
+    Hex,
+    Json,
+}
+
+#[derive(Debug)]
+struct Args {
+    command: String,
+    inputs: Vec<String>,
+    modulus: Option<u64>,
+    root: Option<u64>,
+    format: Format,
+    order: CoefficientOrder,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, Box<dyn Error>> {
+    let mut args = args.iter();
+    let command = args.next().ok_or("missing command")?.clone();
+    let mut res = Args {
+        command,
+        inputs: vec![],
+        modulus: None,
+        root: None,
+        format: Format::Dec,
+        order: CoefficientOrder::Descending,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--modulus" => res.modulus = Some(parse_coef(args.next().ok_or("missing modulus")?)?),
+            "--root" => res.root = Some(parse_coef(args.next().ok_or("missing root")?)?),
+            "--order" => {
+                res.order = match args.next().map(|x| x.as_str()) {
+                    Some("asc") => CoefficientOrder::Ascending,
+                    Some("desc") => CoefficientOrder::Descending,
+                    x => return Err(format!("unknown order {:?}", x).into()),
+                }
+            }
+            "--format" => {
+                res.format = match args.next().map(|x| x.as_str()) {
+                    Some("dec") => Format::Dec,
+                    Some("hex") => Format::Hex,
+                    Some("json") => Format::Json,
+                    x => return Err(format!("unknown format {:?}", x).into()),
+                }
+            }
+            _ => res.inputs.push(arg.clone()),
+        }
+    }
+    Ok(res)
+}
+
+// decimal, or big-endian hex with a `0x` prefix
+fn parse_coef(s: &str) -> Result<u64, Box<dyn Error>> {
+    let s = s.trim().trim_matches('"');
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => {
+            let digits = if digits.len() % 2 == 1 {
+                format!("0{}", digits)
+            } else {
+                digits.to_string()
+            };
+            let bytes = hex::decode(digits)?;
+            if bytes.iter().skip_while(|&&x| x == 0).count() > 8 {
+                return Err(NttError::Overflow.into());
+            }
+            Ok(bytes.iter().fold(0, |acc, &x| (acc << 8) | x as u64))
+        }
+        None => Ok(s.parse()?),
+    }
+}
+
+fn parse_line(line: &str) -> Result<Vec<u64>, Box<dyn Error>> {
+    line.split(|x: char| x == ',' || x.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(parse_coef)
+        .collect()
+}
+
+/// Reads every polynomial in `text`: one per non-empty line, or the rows of
+/// a JSON array of arrays, or a single flat JSON array.
+fn parse_polynomials(text: &str) -> Result<Vec<Vec<u64>>, Box<dyn Error>> {
+    let text = text.trim();
+    if !text.starts_with('[') {
+        return text
+            .lines()
+            .filter(|x| !x.trim().is_empty())
+            .map(parse_line)
+            .collect();
+    }
+    let inner = text
+        .strip_prefix('[')
+        .and_then(|x| x.strip_suffix(']'))
+        .ok_or("unterminated JSON array")?
+        .trim();
+    if !inner.starts_with('[') {
+        return Ok(vec![parse_line(inner)?]);
+    }
+    inner
+        .split(']')
+        .map(|x| x.trim().trim_start_matches(',').trim())
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            let row = x.strip_prefix('[').ok_or("expected a JSON array")?;
+            parse_line(row)
+        })
+        .collect()
+}
+
+fn read_polynomials(inputs: &[String]) -> Result<Vec<Vec<u64>>, Box<dyn Error>> {
+    let mut res = vec![];
+    if inputs.is_empty() {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        res.extend(parse_polynomials(&text)?);
+    }
+    for path in inputs {
+        let text = if path == "-" {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        } else {
+            fs::read_to_string(path)?
+        };
+        res.extend(parse_polynomials(&text)?);
+    }
+    Ok(res)
+}
+
+fn format_coefs(coef: &[u64], format: Format) -> String {
+    match format {
+        Format::Dec => coef.iter().join(" "),
+        Format::Hex => coef.iter().map(|x| format!("{:#x}", x)).join(" "),
+        Format::Json => format!("[{}]", coef.iter().join(", ")),
+    }
+}
+
+fn constants(args: &Args, n: usize, bound: u64) -> Result<Constants<Mod64>, Box<dyn Error>> {
+    match (args.modulus, args.root) {
+        // `w` carries its modulus, so that `intt` can invert it
+        (Some(N), Some(w)) => Ok(Constants {
+            N: Mod64::from(N),
+            w: Mod64::try_new(w, N)?,
+        }),
+        (None, None) => {
+            // the search starts at `bound * n + 1`
+            let start = bound.checked_mul(n as u64).ok_or(NttError::Overflow)?;
+            if start >= 1 << 61 {
+                return Err(NttError::Overflow.into());
+            }
+            Ok(try_working_modulus(Mod64::from(n), Mod64::from(bound))?)
+        }
+        _ => Err("--modulus and --root must be given together".into()),
+    }
+}
+
+// constants for a transform of `a`; for a length that is not a power of two
+// the picked modulus also has the power-of-two root of order at least
+// `2 len - 1` that Bluestein's algorithm needs
+fn transform_constants(args: &Args, a: &[u64]) -> Result<Constants<Mod64>, Box<dyn Error>> {
+    let n = a.len();
+    let bound = a.iter().copied().max().unwrap_or(0) + 1;
+    if args.modulus.is_some() || n.is_power_of_two() {
+        return constants(args, n, bound);
+    }
+    let m = (2 * n - 1).next_power_of_two();
+    let order = n << (m.trailing_zeros() - n.trailing_zeros());
+    let c = constants(args, order, bound)?;
+    Ok(Constants {
+        N: c.N,
+        w: c.w.mod_exp(Mod64::from(order / n), c.N),
+    })
+}
+
+fn one_polynomial(args: &Args) -> Result<Vec<u64>, Box<dyn Error>> {
+    let polys = read_polynomials(&args.inputs)?;
+    match <[Vec<u64>; 1]>::try_from(polys) {
+        Ok([p]) => Ok(p),
+        Err(polys) => Err(format!("expected one polynomial, found {}", polys.len()).into()),
+    }
+}
+
+fn lift(coef: &[u64], c: &Constants<Mod64>) -> Vec<Mod64> {
+    coef.iter().map(|&x| Mod64::from(x).rem(c.N)).collect()
+}
+
+fn values(coef: &[Mod64]) -> Vec<u64> {
+    coef.iter().map(|x| x.v).collect()
+}
+
+fn run(args: &Args) -> Result<String, Box<dyn Error>> {
+    match args.command.as_str() {
+        "mul" => {
+            let polys = read_polynomials(&args.inputs)?;
+            let [a, b] = <[Vec<u64>; 2]>::try_from(polys)
+                .map_err(|polys| format!("expected two polynomials, found {}", polys.len()))?;
+            // every product coefficient is below `max(a) * max(b) * min(len)`
+            let max = |x: &[u64]| x.iter().copied().max().unwrap_or(0);
+            let bound = (max(&a) as u128 * max(&b) as u128 * a.len().min(b.len()) as u128) + 1;
+            let bound = u64::try_from(bound).map_err(|_| NttError::Overflow)?;
+            let n = (a.len() + b.len()).next_power_of_two();
+            let c = constants(args, n, bound)?;
+            let prod = try_fast_mul(
+                Polynomial::with_order(lift(&a, &c), args.order),
+                Polynomial::with_order(lift(&b, &c), args.order),
+                &c,
+            )?;
+            Ok(format_coefs(
+                &values(&prod.to_vec_in(args.order)),
+                args.format,
+            ))
+        }
+        "ntt" | "intt" => {
+            // a modulus picked from the input of `intt` would not match
+            // the one its `ntt` ran with
+            if args.command == "intt" && args.modulus.is_none() {
+                return Err("intt needs the --modulus and --root of its ntt".into());
+            }
+            let a = one_polynomial(args)?;
+            if a.is_empty() {
+                return Err("cannot transform an empty polynomial".into());
+            }
+            let c = transform_constants(args, &a)?;
+            let res = if args.command == "ntt" {
+                try_forward(lift(&a, &c), &c)?
+            } else {
+                try_inverse(lift(&a, &c), &c)?
+            };
+            if args.modulus.is_none() {
+                eprintln!("invert with: intt --modulus {} --root {}", c.N, c.w);
+            }
+            Ok(format_coefs(&values(&res), args.format))
+        }
+        "diff" => {
+            let a = one_polynomial(args)?;
+            let coef = match args.modulus {
+                Some(N) => a
+                    .iter()
+                    .map(|&x| Mod64::try_new(x, N))
+                    .collect::<Result<_, _>>()?,
+                None => a.iter().map(|&x| Mod64::from(x)).collect(),
+            };
+            let res = try_diff(Polynomial::with_order(coef, args.order))?;
+            Ok(format_coefs(
+                &values(&res.to_vec_in(args.order)),
+                args.format,
+            ))
+        }
+        "modulus" => {
+            let [n, bound] = <[&String; 2]>::try_from(args.inputs.iter().collect_vec())
+                .map_err(|_| "expected LEN and BOUND")?;
+            let n: usize = n.parse()?;
+            if n == 0 {
+                return Err("LEN must be positive".into());
+            }
+            let c = constants(args, n, parse_coef(bound)?)?;
+            Ok(format_coefs(&[c.N.v, c.w.v], args.format))
+        }
+        x => Err(format!("unknown command `{}`", x).into()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args[0] == "--help" || args[0] == "-h" {
+        println!("{}", USAGE);
+        return;
+    }
+    match parse_args(&args).and_then(|args| run(&args)) {
+        Ok(out) => println!("{}", out),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::{
+        format_coefs, parse_args, parse_coef, parse_polynomials, run, transform_constants, Format,
+    };
+
+    fn try_run_with(args: &[&str]) -> Result<String, Box<dyn Error>> {
+        let args: Vec<String> = args.iter().map(|x| x.to_string()).collect();
+        run(&parse_args(&args)?)
+    }
+
+    fn run_with(args: &[&str]) -> String {
+        try_run_with(args).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_coef("0x1f").unwrap(), 31);
+        assert_eq!(parse_coef("\"0X100\"").unwrap(), 256);
+        assert_eq!(parse_coef("42").unwrap(), 42);
+        assert!(parse_coef("0x10000000000000000").is_err());
+        assert_eq!(
+            parse_polynomials("1 2 3\n\n0x4, 5\n").unwrap(),
+            vec![vec![1, 2, 3], vec![4, 5]]
+        );
+        assert_eq!(
+            parse_polynomials("[[1, 2], [\"0x3\"]]").unwrap(),
+            vec![vec![1, 2], vec![3]]
+        );
+        assert_eq!(parse_polynomials("[7, 8]").unwrap(), vec![vec![7, 8]]);
+        assert_eq!(format_coefs(&[1, 255], Format::Hex), "0x1 0xff");
+        assert_eq!(format_coefs(&[1, 2], Format::Json), "[1, 2]");
+    }
+
+    #[test]
+    fn test_commands() {
+        let dir = std::env::temp_dir().join(format!("fast-ntt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.txt");
+        let b = dir.join("b.json");
+        std::fs::write(&a, "1 2 3\n").unwrap();
+        let z = dir.join("z.txt");
+        std::fs::write(&b, "[1, 0x2]").unwrap();
+        std::fs::write(&z, "0 0").unwrap();
+        let (a, b, z) = (
+            a.to_str().unwrap(),
+            b.to_str().unwrap(),
+            z.to_str().unwrap(),
+        );
+
+        assert_eq!(run_with(&["mul", a, b]), "1 4 7 6");
+        assert_eq!(run_with(&["mul", a, z]), "0");
+        assert_eq!(run_with(&["diff", a]), "2 2");
+        assert_eq!(run_with(&["diff", a, "--order", "asc"]), "2 6");
+        assert_eq!(run_with(&["mul", a, b, "--order", "asc"]), "1 4 7 6");
+        assert_eq!(run_with(&["modulus", "8", "100"]), "809 239");
+
+        let c = ["--modulus", "17", "--root", "16"];
+        let f = run_with(&[&["ntt", b], &c[..]].concat());
+        assert_eq!(f, "3 16");
+        let g = dir.join("f.txt");
+        std::fs::write(&g, f).unwrap();
+        let g = g.to_str().unwrap();
+        assert_eq!(run_with(&[&["intt", g], &c[..]].concat()), "1 2");
+        assert!(try_run_with(&["intt", g]).is_err());
+        std::fs::write(g, "1 2 3 4").unwrap();
+        let c = ["--modulus", "17", "--root", "4"];
+        let f = run_with(&[&["ntt", g], &c[..]].concat());
+        std::fs::write(g, f).unwrap();
+        assert_eq!(run_with(&[&["intt", g], &c[..]].concat()), "1 2 3 4");
+
+        // length 7 goes through Bluestein's algorithm
+        let s = dir.join("s.json");
+        std::fs::write(&s, "[1, 2, 3, 4, 5, 6, 7]").unwrap();
+        let s = s.to_str().unwrap();
+        let f = run_with(&["ntt", s]);
+        assert_eq!(f.split(' ').count(), 7);
+        let args = parse_args(&["ntt".to_string()]).unwrap();
+        let c = transform_constants(&args, &[1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let (N, w) = (c.N.v.to_string(), c.w.v.to_string());
+        std::fs::write(g, f).unwrap();
+        assert_eq!(
+            run_with(&["intt", g, "--modulus", &N, "--root", &w]),
+            "1 2 3 4 5 6 7"
+        );
+
+        let e = dir.join("e.json");
+        std::fs::write(&e, "[]").unwrap();
+        let e = e.to_str().unwrap();
+        assert_eq!(
+            try_run_with(&["ntt", e]).unwrap_err().to_string(),
+            "cannot transform an empty polynomial"
+        );
+        assert_eq!(
+            try_run_with(&["modulus", "0", "100"])
+                .unwrap_err()
+                .to_string(),
+            "LEN must be positive"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
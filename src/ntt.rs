@@ -1,4 +1,9 @@
-use crate::{numbers::BigInt, polynomial::PolynomialFieldElement, prime::is_prime};
+use crate::{
+    error::NttError,
+    numbers::{BigInt, Fp, NttFieldElement, NttPrime},
+    polynomial::{checked_mul, PolynomialFieldElement},
+    prime::is_prime,
+};
 use crypto_bigint::Invert;
 use itertools::Itertools;
 use rayon::prelude::*;
@@ -9,6 +14,84 @@ pub struct Constants<T: PolynomialFieldElement> {
     pub w: T,
 }
 
+impl<T: PolynomialFieldElement> Constants<T> {
+    /// Multiplicative order of `w`, which must be a power of two.
+    pub fn order(&self) -> usize {
+        self.try_order().unwrap()
+    }
+
+    pub fn try_order(&self) -> Result<usize, NttError> {
+        let ONE = T::from(1);
+        let TWO = T::from(2);
+        let mut x = self.w.rem(self.N);
+        let mut order = 1;
+        while x != ONE {
+            if order >= (1 << (usize::BITS - 1)) {
+                return Err(NttError::RootNotPowerOfTwoOrder);
+            }
+            x = x.mod_exp(TWO, self.N);
+            order <<= 1;
+        }
+        Ok(order)
+    }
+
+    /// Lifts `x` into the residue ring modulo `N`, so that subsequent
+    /// arithmetic on it is reduced.
+    pub fn reduce(&self, x: T) -> T {
+        let mut x = x.rem(self.N);
+        let _ = x.set_mod(self.N);
+        x
+    }
+
+    /// Primitive `n`-th root of unity derived from `w`.
+    pub fn root_of_order(&self, n: usize) -> T {
+        self.try_root_of_order(n).unwrap()
+    }
+
+    pub fn try_root_of_order(&self, n: usize) -> Result<T, NttError> {
+        if !n.is_power_of_two() {
+            return Err(NttError::NonPowerOfTwoLength(n));
+        }
+        self.try_root_with_order(n, self.try_order()?)
+    }
+
+    // as `try_root_of_order`, with the order of `w` already known
+    fn try_root_with_order(&self, n: usize, order: usize) -> Result<T, NttError> {
+        if !n.is_power_of_two() {
+            return Err(NttError::NonPowerOfTwoLength(n));
+        }
+        if !order.is_multiple_of(n) {
+            return Err(NttError::NoRootOfUnity(n));
+        }
+        Ok(self.w.mod_exp(T::from(order / n), self.N))
+    }
+}
+
+impl<P: NttPrime> Constants<Fp<P>> {
+    /// Constants for transforms of length `n`, read off the baked-in root
+    /// of unity instead of searching for a modulus.
+    pub fn for_size(n: usize) -> Self {
+        Self::try_for_size(n).unwrap()
+    }
+
+    pub fn try_for_size(n: usize) -> Result<Self, NttError> {
+        let totient = P::MODULUS - 1;
+        if n == 0 || totient % n as u64 != 0 {
+            return Err(NttError::NoRootOfUnity(n));
+        }
+        let w = if n.is_power_of_two() {
+            let log_n = n.trailing_zeros();
+            Fp::<P>::from(P::ROOT).pow(1_u128 << (P::TWO_ADICITY - log_n))
+        } else {
+            Fp::<P>::from(P::GENERATOR).pow((totient / n as u64) as u128)
+        };
+        Ok(Constants {
+            N: Fp::modulus(),
+            w,
+        })
+    }
+}
+
 fn prime_factors<T: PolynomialFieldElement>(a: T) -> Vec<T> {
     let mut ans: Vec<T> = Vec::new();
     let ZERO = T::from(0);
@@ -48,17 +131,44 @@ fn is_primitive_root<T: PolynomialFieldElement>(a: T, deg: T, N: T) -> bool {
 }
 
 pub fn working_modulus<T: PolynomialFieldElement>(n: T, M: T) -> Constants<T> {
+    try_working_modulus(n, M).unwrap()
+}
+
+pub fn try_working_modulus<T: PolynomialFieldElement>(
+    n: T,
+    M: T,
+) -> Result<Constants<T>, NttError> {
     let ZERO = T::from(0);
     let ONE = T::from(1);
+    if n == ZERO {
+        return Err(NttError::NonPowerOfTwoLength(0));
+    }
     let mut N = M;
     if N >= ONE {
-        N = N * n + ONE;
-        while !is_prime(N) {
-            N += n;
+        N = checked_mul(N, n)? + ONE;
+        loop {
+            // stop once the candidates leave the moduli the type accepts
+            let mut probe = ONE;
+            if probe.set_mod(N) == Err(NttError::InvalidModulus) {
+                return Err(NttError::Overflow);
+            }
+            if is_prime(N) {
+                break;
+            }
+            let next = N + n;
+            if next < N {
+                return Err(NttError::Overflow);
+            }
+            N = next;
         }
     }
+    if N < M {
+        return Err(NttError::Overflow);
+    }
+    if !is_prime(N) {
+        return Err(NttError::ModulusNotPrime);
+    }
     let totient = N - ONE;
-    assert!(N >= M);
     let mut gen = T::from(0);
     let mut g = T::from(2);
     while g < N {
@@ -68,12 +178,14 @@ pub fn working_modulus<T: PolynomialFieldElement>(n: T, M: T) -> Constants<T> {
         }
         g += ONE;
     }
-    assert!(gen > ZERO);
+    if gen == ZERO {
+        return Err(NttError::ModulusNotPrime);
+    }
     let w = gen.mod_exp(totient / n, N);
-    Constants { N, w }
+    Ok(Constants { N, w })
 }
 
-fn order_reverse<T: PolynomialFieldElement>(inp: &mut Vec<T>) {
+fn order_reverse<T>(inp: &mut [T]) {
     let mut j = 0;
     let n = inp.len();
     (1..n).for_each(|i| {
@@ -91,13 +203,9 @@ fn order_reverse<T: PolynomialFieldElement>(inp: &mut Vec<T>) {
 }
 
 #[cfg(feature = "parallel")]
-fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T> {
-    assert!(inp.len().is_power_of_two());
-    let mut inp = inp.clone();
-    let N = inp.len();
-    let MOD = T::from(c.N);
+fn twiddles<T: PolynomialFieldElement>(w: T, n: usize, MOD: T) -> Vec<T> {
     let ONE = T::from(1);
-    let mut pre: Vec<T> = vec![ONE; N / 2];
+    let mut pre: Vec<T> = vec![ONE; n / 2];
     let CHUNK_COUNT = 128;
     let chunk_count = T::from(CHUNK_COUNT);
 
@@ -106,12 +214,47 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
         .for_each(|(i, arr)| arr[0] = w.mod_exp(T::from(i) * chunk_count, MOD));
     pre.par_chunks_mut(CHUNK_COUNT).for_each(|x| {
         (1..x.len()).for_each(|y| {
-            let _x = x.to_vec();
             x[y] = (w * x[y - 1]).rem(MOD);
         })
     });
-    order_reverse(&mut inp);
+    pre
+}
+
+#[cfg(not(feature = "parallel"))]
+fn twiddles<T: PolynomialFieldElement>(w: T, n: usize, MOD: T) -> Vec<T> {
+    let ONE = T::from(1);
+    let mut pre: Vec<T> = vec![ONE; n / 2];
+    let CHUNK_COUNT = 128;
+    let chunk_count = T::from(CHUNK_COUNT);
+
+    pre.chunks_mut(CHUNK_COUNT)
+        .enumerate()
+        .for_each(|(i, arr)| arr[0] = w.mod_exp(T::from(i) * chunk_count, MOD));
+    pre.chunks_mut(CHUNK_COUNT).for_each(|x| {
+        (1..x.len()).for_each(|y| {
+            x[y] = (w * x[y - 1]).rem(MOD);
+        })
+    });
+    pre
+}
+
+fn butterfly<T: PolynomialFieldElement>(lo: &mut T, hi: &mut T, tw: T, MOD: T) {
+    *hi = (*hi * tw).rem(MOD);
+    let neg = if *lo < *hi {
+        (MOD + *lo) - *hi
+    } else {
+        *lo - *hi
+    };
+    *lo = if *lo + *hi >= MOD {
+        (*lo + *hi) - MOD
+    } else {
+        *lo + *hi
+    };
+    *hi = neg;
+}
 
+#[cfg(feature = "parallel")]
+fn butterflies<T: PolynomialFieldElement>(inp: &mut [T], pre: &[T], MOD: T) {
     let mut gap = 1;
 
     while gap < inp.len() {
@@ -122,47 +265,15 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
                 .zip(hi)
                 .enumerate()
                 .for_each(|(idx, (lo, hi))| {
-                    *hi = (*hi * pre[nchunks * idx]).rem(MOD);
-                    let neg = if *lo < *hi {
-                        (MOD + *lo) - *hi
-                    } else {
-                        *lo - *hi
-                    };
-                    *lo = if *lo + *hi >= MOD {
-                        (*lo + *hi) - MOD
-                    } else {
-                        *lo + *hi
-                    };
-                    *hi = neg;
+                    butterfly(lo, hi, pre[nchunks * idx], MOD);
                 });
         });
         gap *= 2;
     }
-    inp
 }
 
 #[cfg(not(feature = "parallel"))]
-fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T> {
-    assert!(inp.len().is_power_of_two());
-    let mut inp = inp.clone();
-    let N = inp.len();
-    let MOD = T::from(c.N);
-    let ONE = T::from(1);
-    let mut pre: Vec<T> = vec![ONE; N / 2];
-    let CHUNK_COUNT = 128;
-    let chunk_count = T::from(CHUNK_COUNT);
-
-    pre.chunks_mut(CHUNK_COUNT)
-        .enumerate()
-        .for_each(|(i, arr)| arr[0] = w.mod_exp(T::from(i) * chunk_count, MOD));
-    pre.chunks_mut(CHUNK_COUNT).for_each(|x| {
-        (1..x.len()).for_each(|y| {
-            let _x = x.to_vec();
-            x[y] = (w * x[y - 1]).rem(MOD);
-        })
-    });
-    order_reverse(&mut inp);
-
+fn butterflies<T: PolynomialFieldElement>(inp: &mut [T], pre: &[T], MOD: T) {
     let mut gap = 1;
 
     while gap < inp.len() {
@@ -173,59 +284,577 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
                 .zip(hi)
                 .enumerate()
                 .for_each(|(idx, (lo, hi))| {
-                    *hi = (*hi * pre[nchunks * idx]).rem(MOD);
-                    let neg = if *lo < *hi {
-                        (MOD + *lo) - *hi
-                    } else {
-                        *lo - *hi
-                    };
-                    *lo = if *lo + *hi >= MOD {
-                        (*lo + *hi) - MOD
-                    } else {
-                        *lo + *hi
-                    };
-                    *hi = neg;
+                    butterfly(lo, hi, pre[nchunks * idx], MOD);
                 });
         });
         gap *= 2;
     }
-    inp
 }
 
-pub fn forward<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
-    fft(inp, c, c.w)
+#[cfg(feature = "parallel")]
+fn scale<T: PolynomialFieldElement>(inp: &mut [T], k: T, MOD: T) {
+    inp.par_iter_mut().for_each(|x| *x = (k * (*x)).rem(MOD));
 }
 
+#[cfg(not(feature = "parallel"))]
+fn scale<T: PolynomialFieldElement>(inp: &mut [T], k: T, MOD: T) {
+    inp.iter_mut().for_each(|x| *x = (k * (*x)).rem(MOD));
+}
+
+// inp[i] *= psi^i
 #[cfg(feature = "parallel")]
-pub fn inverse<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
+fn twist<T: PolynomialFieldElement>(inp: &mut [T], psi: T, MOD: T) {
+    let CHUNK_COUNT = 128;
+    inp.par_chunks_mut(CHUNK_COUNT)
+        .enumerate()
+        .for_each(|(i, arr)| {
+            let mut k = psi.mod_exp(T::from(i * CHUNK_COUNT), MOD);
+            arr.iter_mut().for_each(|x| {
+                *x = (k * (*x)).rem(MOD);
+                k = (k * psi).rem(MOD);
+            });
+        });
+}
+
+#[cfg(not(feature = "parallel"))]
+fn twist<T: PolynomialFieldElement>(inp: &mut [T], psi: T, MOD: T) {
+    let mut k = psi.mod_exp(T::from(0), MOD);
+    inp.iter_mut().for_each(|x| {
+        *x = (k * (*x)).rem(MOD);
+        k = (k * psi).rem(MOD);
+    });
+}
+
+// same butterflies as `butterflies`, but twiddles are generated on the fly
+#[cfg(feature = "parallel")]
+fn butterflies_in_place<T: PolynomialFieldElement>(inp: &mut [T], w: T, MOD: T) {
+    let n = inp.len();
+    let CHUNK_COUNT = 128;
+    let mut gap = 1;
+
+    while gap < n {
+        let w_len = w.mod_exp(T::from(n / (2 * gap)), MOD);
+        inp.par_chunks_mut(2 * gap).for_each(|cxi| {
+            let (lo, hi) = cxi.split_at_mut(gap);
+            lo.par_chunks_mut(CHUNK_COUNT)
+                .zip(hi.par_chunks_mut(CHUNK_COUNT))
+                .enumerate()
+                .for_each(|(i, (lo, hi))| {
+                    let mut tw = w_len.mod_exp(T::from(i * CHUNK_COUNT), MOD);
+                    lo.iter_mut().zip(hi).for_each(|(lo, hi)| {
+                        butterfly(lo, hi, tw, MOD);
+                        tw = (tw * w_len).rem(MOD);
+                    });
+                });
+        });
+        gap *= 2;
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn butterflies_in_place<T: PolynomialFieldElement>(inp: &mut [T], w: T, MOD: T) {
+    let n = inp.len();
+    let mut gap = 1;
+
+    while gap < n {
+        let w_len = w.mod_exp(T::from(n / (2 * gap)), MOD);
+        let ONE = w_len.mod_exp(T::from(0), MOD);
+        inp.chunks_mut(2 * gap).for_each(|cxi| {
+            let (lo, hi) = cxi.split_at_mut(gap);
+            let mut tw = ONE;
+            lo.iter_mut().zip(hi).for_each(|(lo, hi)| {
+                butterfly(lo, hi, tw, MOD);
+                tw = (tw * w_len).rem(MOD);
+            });
+        });
+        gap *= 2;
+    }
+}
+
+fn fft<T: PolynomialFieldElement>(inp: &mut [T], c: &Constants<T>, w: T) {
+    assert!(inp.len().is_power_of_two());
+    let MOD = T::from(c.N);
+    order_reverse(inp);
+    butterflies_in_place(inp, w, MOD);
+}
+
+pub fn forward_in_place<T: PolynomialFieldElement>(inp: &mut [T], c: &Constants<T>) {
+    try_forward_in_place(inp, c).unwrap()
+}
+
+pub fn inverse_in_place<T: PolynomialFieldElement>(inp: &mut [T], c: &Constants<T>) {
+    try_inverse_in_place(inp, c).unwrap()
+}
+
+pub fn try_forward_in_place<T: PolynomialFieldElement>(
+    inp: &mut [T],
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
+    fft(inp, c, c.w);
+    Ok(())
+}
+
+pub fn try_inverse_in_place<T: PolynomialFieldElement>(
+    inp: &mut [T],
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
     let mut inv = T::from(inp.len());
     let _ = inv.set_mod(c.N);
     let inv = inv.invert();
     let w = c.w.invert();
-    let mut res = fft(inp, c, w);
-    res.par_iter_mut().for_each(|x| *x = (inv * (*x)).rem(c.N));
-    res
+    fft(inp, c, w);
+    scale(inp, inv, c.N);
+    Ok(())
+}
+
+// n = 2^a 3^b 5^c
+fn is_smooth(n: usize) -> bool {
+    let mut n = n;
+    for p in [2, 3, 5] {
+        while n.is_multiple_of(p) {
+            n /= p;
+        }
+    }
+    n == 1
+}
+
+// decimation in time, splitting off the smallest of 2, 3 and 5 dividing the
+// length and combining with a naive DFT of that radix
+fn mixed_radix<T: PolynomialFieldElement>(inp: &[T], c: &Constants<T>, w: T) -> Vec<T> {
+    let n = inp.len();
+    if n == 1 {
+        return inp.to_vec();
+    }
+    let p = [2, 3, 5]
+        .into_iter()
+        .find(|&p| n.is_multiple_of(p))
+        .unwrap();
+    let m = n / p;
+    let wp = w.mod_exp(T::from(p), c.N);
+    let sub: Vec<Vec<T>> = (0..p)
+        .map(|j| mixed_radix(&inp.iter().skip(j).step_by(p).cloned().collect_vec(), c, wp))
+        .collect();
+
+    let ONE = c.reduce(T::from(1));
+    let ZERO = c.reduce(T::from(0));
+    let root_p = w.mod_exp(T::from(m), c.N);
+    let mut roots = vec![ONE; p];
+    (1..p).for_each(|i| roots[i] = roots[i - 1] * root_p);
+
+    let mut out = vec![ZERO; n];
+    let mut wk = ONE;
+    (0..m).for_each(|k| {
+        let mut wjk = ONE;
+        let t: Vec<T> = (0..p)
+            .map(|j| {
+                let x = sub[j][k] * wjk;
+                wjk *= wk;
+                x
+            })
+            .collect();
+        (0..p).for_each(|q| {
+            out[k + m * q] = (0..p).fold(ZERO, |acc, j| acc + t[j] * roots[(j * q) % p]);
+        });
+        wk *= w;
+    });
+    out
+}
+
+// primitive `m`-th root of unity for power-of-two `m`, taken from a
+// quadratic non-residue
+pub(crate) fn two_adic_root<T: PolynomialFieldElement>(N: T, m: usize) -> Result<T, NttError> {
+    let ONE = T::from(1);
+    let totient = N - ONE;
+    if !totient.rem(T::from(m)).is_zero() {
+        return Err(NttError::NoRootOfUnity(m));
+    }
+    let half = totient / T::from(2);
+    let mut g = T::from(2);
+    while g.mod_exp(half, N) == ONE {
+        g += ONE;
+    }
+    Ok(g.mod_exp(totient / T::from(m), N))
+}
+
+// chirp-z: w^(jk) = t(j + k) / (t(j) t(k)) with t(i) = w^(i(i-1)/2) turns
+// the DFT into a correlation, evaluated with a power-of-two NTT
+fn bluestein<T: PolynomialFieldElement>(
+    inp: &[T],
+    c: &Constants<T>,
+    w: T,
+) -> Result<Vec<T>, NttError> {
+    let n = inp.len();
+    let m = (2 * n - 1).next_power_of_two();
+    let cm = Constants {
+        N: c.N,
+        w: two_adic_root(c.N, m)?,
+    };
+
+    let ONE = c.reduce(T::from(1));
+    let ZERO = c.reduce(T::from(0));
+    let w_inv = c.reduce(w).invert();
+    let mut chirp = Vec::with_capacity(2 * n - 1);
+    let mut chirp_inv = Vec::with_capacity(n);
+    let (mut t, mut wi, mut t_inv, mut wi_inv) = (ONE, ONE, ONE, ONE);
+    (0..2 * n - 1).for_each(|i| {
+        chirp.push(t);
+        t *= wi;
+        wi *= w;
+        if i < n {
+            chirp_inv.push(t_inv);
+            t_inv *= wi_inv;
+            wi_inv *= w_inv;
+        }
+    });
+
+    let mut a = vec![ZERO; m];
+    let mut b = vec![ZERO; m];
+    (0..n).for_each(|j| a[n - 1 - j] = c.reduce(inp[j]) * chirp_inv[j]);
+    b[..2 * n - 1].copy_from_slice(&chirp);
+    forward_in_place(&mut a, &cm);
+    forward_in_place(&mut b, &cm);
+    a.iter_mut()
+        .zip(b)
+        .for_each(|(x, y)| *x = (*x * y).rem(c.N));
+    inverse_in_place(&mut a, &cm);
+
+    Ok((0..n)
+        .map(|k| (a[n - 1 + k] * chirp_inv[k]).rem(c.N))
+        .collect())
+}
+
+// DFT of any length with `w` a primitive root of that order
+fn dft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Result<Vec<T>, NttError> {
+    let n = inp.len();
+    if n == 0 {
+        Ok(inp)
+    } else if n.is_power_of_two() {
+        let mut inp = inp;
+        fft(&mut inp, c, w);
+        Ok(inp)
+    } else if is_smooth(n) {
+        Ok(mixed_radix(&inp, c, w))
+    } else {
+        bluestein(&inp, c, w)
+    }
+}
+
+/// Transforms of any length `n`, with `c.w` a primitive `n`-th root of
+/// unity. Lengths of the form `2^a 3^b 5^c` use a mixed-radix transform;
+/// any other length uses Bluestein's algorithm, which additionally needs
+/// `N - 1` to be divisible by the power of two at or above `2n - 1`.
+pub fn forward<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
+    try_forward(inp, c).unwrap()
 }
 
-#[cfg(not(feature = "parallel"))]
 pub fn inverse<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
+    try_inverse(inp, c).unwrap()
+}
+
+pub fn try_forward<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    dft(inp, c, c.w)
+}
+
+pub fn try_inverse<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
     let mut inv = T::from(inp.len());
     let _ = inv.set_mod(c.N);
     let inv = inv.invert();
-    let w = c.w.invert();
-    let mut res = fft(inp, c, w);
-    res.iter_mut().for_each(|x| *x = (inv * (*x)).rem(c.N));
-    res
+    let mut res = dft(inp, c, c.w.invert())?;
+    scale(&mut res, inv, c.N);
+    Ok(res)
+}
+
+/// Negacyclic transform of `inp`, read as ascending coefficients of an
+/// element of `Z_N[X]/(X^n + 1)`. `c.w` must have a power-of-two order
+/// divisible by `2n`.
+pub fn negacyclic_forward_in_place<T: PolynomialFieldElement>(inp: &mut [T], c: &Constants<T>) {
+    try_negacyclic_forward_in_place(inp, c).unwrap()
+}
+
+pub fn negacyclic_inverse_in_place<T: PolynomialFieldElement>(inp: &mut [T], c: &Constants<T>) {
+    try_negacyclic_inverse_in_place(inp, c).unwrap()
+}
+
+pub fn try_negacyclic_forward_in_place<T: PolynomialFieldElement>(
+    inp: &mut [T],
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
+    let MOD = c.N;
+    let psi = c.try_root_of_order(2 * inp.len())?;
+    twist(inp, psi, MOD);
+    fft(inp, c, psi.mod_exp(T::from(2), MOD));
+    Ok(())
+}
+
+pub fn try_negacyclic_inverse_in_place<T: PolynomialFieldElement>(
+    inp: &mut [T],
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
+    let MOD = c.N;
+    let psi_inv = c.try_root_of_order(2 * inp.len())?.invert();
+    fft(inp, c, psi_inv.mod_exp(T::from(2), MOD));
+    let mut inv = T::from(inp.len());
+    let _ = inv.set_mod(c.N);
+    scale(inp, inv.invert(), MOD);
+    twist(inp, psi_inv, MOD);
+    Ok(())
+}
+
+pub fn negacyclic_forward<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
+    try_negacyclic_forward(inp, c).unwrap()
+}
+
+pub fn negacyclic_inverse<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
+    try_negacyclic_inverse(inp, c).unwrap()
+}
+
+pub fn try_negacyclic_forward<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let mut inp = inp;
+    try_negacyclic_forward_in_place(&mut inp, c)?;
+    Ok(inp)
+}
+
+pub fn try_negacyclic_inverse<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let mut inp = inp;
+    try_negacyclic_inverse_in_place(&mut inp, c)?;
+    Ok(inp)
+}
+
+#[cfg(feature = "parallel")]
+fn for_each_row<T: Send, F: Fn(&mut [T]) + Send + Sync>(matrix: &mut [T], n: usize, f: F) {
+    matrix.par_chunks_mut(n).for_each(f);
+}
+
+#[cfg(not(feature = "parallel"))]
+fn for_each_row<T, F: Fn(&mut [T])>(matrix: &mut [T], n: usize, f: F) {
+    matrix.chunks_mut(n).for_each(f);
+}
+
+/// Forward transform of every length-`n` row of the row-major `matrix` in
+/// place, all sharing one `NttPlan`; `n` must be a power of two.
+pub fn forward_batch<T: PolynomialFieldElement>(matrix: &mut [T], n: usize, c: &Constants<T>) {
+    try_forward_batch(matrix, n, c).unwrap()
+}
+
+pub fn inverse_batch<T: PolynomialFieldElement>(matrix: &mut [T], n: usize, c: &Constants<T>) {
+    try_inverse_batch(matrix, n, c).unwrap()
+}
+
+pub fn try_forward_batch<T: PolynomialFieldElement>(
+    matrix: &mut [T],
+    n: usize,
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    NttPlan::try_new(c, n)?.try_forward_batch(matrix)
+}
+
+pub fn try_inverse_batch<T: PolynomialFieldElement>(
+    matrix: &mut [T],
+    n: usize,
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    NttPlan::try_new(c, n)?.try_inverse_batch(matrix)
+}
+
+/// Precomputed twiddle tables, bit-reversal permutation and `n^-1` for
+/// repeated transforms of length `n`.
+#[derive(Debug, Clone)]
+pub struct NttPlan<T: PolynomialFieldElement> {
+    pub n: usize,
+    pub c: Constants<T>,
+    fwd: Vec<T>,
+    inv: Vec<T>,
+    rev: Vec<usize>,
+    n_inv: T,
+}
+
+impl<T: PolynomialFieldElement> NttPlan<T> {
+    /// `c.w` must have a power-of-two order divisible by `n`.
+    pub fn new(c: &Constants<T>, n: usize) -> Self {
+        NttPlan::try_new(c, n).unwrap()
+    }
+
+    pub fn try_new(c: &Constants<T>, n: usize) -> Result<Self, NttError> {
+        if !n.is_power_of_two() {
+            return Err(NttError::NonPowerOfTwoLength(n));
+        }
+        NttPlan::try_with_order(c, n, c.try_order()?)
+    }
+
+    /// As `new`, with `order` the order of `c.w` (`c.order()`), so plans of
+    /// several lengths over the same constants skip searching for it.
+    pub fn with_order(c: &Constants<T>, n: usize, order: usize) -> Self {
+        NttPlan::try_with_order(c, n, order).unwrap()
+    }
+
+    pub fn try_with_order(c: &Constants<T>, n: usize, order: usize) -> Result<Self, NttError> {
+        let c = Constants {
+            N: c.N,
+            w: c.try_root_with_order(n, order)?,
+        };
+
+        let mut n_inv = T::from(n);
+        let _ = n_inv.set_mod(c.N);
+        let n_inv = n_inv.invert();
+
+        let mut rev: Vec<usize> = (0..n).collect();
+        order_reverse(&mut rev);
+
+        Ok(NttPlan {
+            n,
+            fwd: twiddles(c.w, n, c.N),
+            inv: twiddles(c.w.invert(), n, c.N),
+            rev,
+            n_inv,
+            c,
+        })
+    }
+
+    fn check_len(&self, inp: &[T]) -> Result<(), NttError> {
+        if inp.len() != self.n {
+            return Err(NttError::LengthMismatch {
+                expected: self.n,
+                found: inp.len(),
+            });
+        }
+        Ok(())
+    }
+
+    // the matrix must hold a whole number of rows
+    fn check_rows(&self, matrix: &[T]) -> Result<(), NttError> {
+        if !matrix.len().is_multiple_of(self.n) {
+            return Err(NttError::LengthMismatch {
+                expected: matrix.len().next_multiple_of(self.n),
+                found: matrix.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn permute(&self, inp: &mut [T]) {
+        self.rev.iter().enumerate().for_each(|(i, &j)| {
+            if i < j {
+                inp.swap(i, j);
+            }
+        });
+    }
+
+    pub fn forward_in_place(&self, inp: &mut [T]) {
+        self.try_forward_in_place(inp).unwrap()
+    }
+
+    pub fn inverse_in_place(&self, inp: &mut [T]) {
+        self.try_inverse_in_place(inp).unwrap()
+    }
+
+    pub fn try_forward_in_place(&self, inp: &mut [T]) -> Result<(), NttError> {
+        self.check_len(inp)?;
+        self.permute(inp);
+        butterflies(inp, &self.fwd, self.c.N);
+        Ok(())
+    }
+
+    pub fn try_inverse_in_place(&self, inp: &mut [T]) -> Result<(), NttError> {
+        self.check_len(inp)?;
+        self.permute(inp);
+        butterflies(inp, &self.inv, self.c.N);
+        scale(inp, self.n_inv, self.c.N);
+        Ok(())
+    }
+
+    /// Transforms every length-`n` row of the row-major `matrix` in place,
+    /// in parallel across rows with the `parallel` feature.
+    pub fn forward_batch(&self, matrix: &mut [T]) {
+        self.try_forward_batch(matrix).unwrap()
+    }
+
+    pub fn inverse_batch(&self, matrix: &mut [T]) {
+        self.try_inverse_batch(matrix).unwrap()
+    }
+
+    pub fn try_forward_batch(&self, matrix: &mut [T]) -> Result<(), NttError> {
+        self.check_rows(matrix)?;
+        for_each_row(matrix, self.n, |row| {
+            self.permute(row);
+            butterflies(row, &self.fwd, self.c.N);
+        });
+        Ok(())
+    }
+
+    pub fn try_inverse_batch(&self, matrix: &mut [T]) -> Result<(), NttError> {
+        self.check_rows(matrix)?;
+        for_each_row(matrix, self.n, |row| {
+            self.permute(row);
+            butterflies(row, &self.inv, self.c.N);
+            scale(row, self.n_inv, self.c.N);
+        });
+        Ok(())
+    }
+
+    pub fn forward(&self, inp: Vec<T>) -> Vec<T> {
+        self.try_forward(inp).unwrap()
+    }
+
+    pub fn inverse(&self, inp: Vec<T>) -> Vec<T> {
+        self.try_inverse(inp).unwrap()
+    }
+
+    pub fn try_forward(&self, inp: Vec<T>) -> Result<Vec<T>, NttError> {
+        let mut inp = inp;
+        self.try_forward_in_place(&mut inp)?;
+        Ok(inp)
+    }
+
+    pub fn try_inverse(&self, inp: Vec<T>) -> Result<Vec<T>, NttError> {
+        let mut inp = inp;
+        self.try_inverse_in_place(&mut inp)?;
+        Ok(inp)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fmt::Debug;
+
     use rand::Rng;
     use rayon::{iter::ParallelIterator, slice::ParallelSliceMut};
 
     use crate::{
-        ntt::{forward, inverse, working_modulus},
-        numbers::BigInt,
+        error::NttError,
+        ntt::{
+            forward, forward_batch, forward_in_place, inverse, inverse_batch, inverse_in_place,
+            negacyclic_forward, negacyclic_inverse, try_forward_in_place, try_inverse_batch,
+            try_working_modulus, working_modulus, Constants, NttPlan,
+        },
+        numbers::{
+            BigInt, Fp, Fp998244353, FpBabyBear, Goldilocks, Mod64, NttFieldElement, NttPrime,
+            P4293918721,
+        },
+        polynomial::PolynomialFieldElement,
     };
 
     #[test]
@@ -244,6 +873,225 @@ mod tests {
         v.iter().zip(inverse).for_each(|(&a, b)| assert_eq!(a, b));
     }
 
+    #[test]
+    fn test_forward_mod64() {
+        let n = 64;
+        let v: Vec<Mod64> = (0..n).map(Mod64::from).collect();
+        let c = working_modulus(Mod64::from(n), Mod64::from(1_u64 << 40));
+        let forward = forward(v.clone(), &c);
+        assert_eq!(inverse(forward, &c), v);
+    }
+
+    fn roundtrip_for_size<P: NttPrime>(n: usize) {
+        let v: Vec<Fp<P>> = (0..n).map(Fp::from).collect();
+        let c = Constants::<Fp<P>>::for_size(n);
+        let forward = forward(v.clone(), &c);
+        assert_eq!(inverse(forward, &c), v);
+    }
+
+    #[test]
+    fn test_for_size() {
+        roundtrip_for_size::<Goldilocks>(1 << 10);
+        roundtrip_for_size::<P4293918721>(1 << 10);
+        let c = Constants::<Fp998244353>::for_size(1 << 4);
+        assert_eq!(c.order(), 1 << 4);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Constants::<Fp998244353>::try_for_size(1 << 24).unwrap_err(),
+            NttError::NoRootOfUnity(1 << 24)
+        );
+        let c = Constants::<Fp998244353>::for_size(1 << 4);
+        assert_eq!(
+            c.try_root_of_order(1 << 5).unwrap_err(),
+            NttError::NoRootOfUnity(1 << 5)
+        );
+        assert_eq!(
+            NttPlan::try_new(&c, 6).unwrap_err(),
+            NttError::NonPowerOfTwoLength(6)
+        );
+        // 3 generates the whole multiplicative group, of order 2^23 * 7 * 17
+        let odd = Constants {
+            N: Fp998244353::modulus(),
+            w: Fp998244353::from(3),
+        };
+        assert_eq!(
+            odd.try_order().unwrap_err(),
+            NttError::RootNotPowerOfTwoOrder
+        );
+
+        let mut buf = vec![Fp998244353::from(1); 6];
+        assert_eq!(
+            try_forward_in_place(&mut buf, &c).unwrap_err(),
+            NttError::NonPowerOfTwoLength(6)
+        );
+        let plan = NttPlan::new(&c, 1 << 3);
+        assert_eq!(
+            plan.try_forward(buf).unwrap_err(),
+            NttError::LengthMismatch {
+                expected: 8,
+                found: 6
+            }
+        );
+        assert_eq!(
+            try_working_modulus(BigInt::from(4), BigInt::from(0)).unwrap_err(),
+            NttError::ModulusNotPrime
+        );
+        assert_eq!(
+            try_working_modulus(Mod64::from(0), Mod64::from(100)).unwrap_err(),
+            NttError::NonPowerOfTwoLength(0)
+        );
+        // 2^62 - k 2^36 + 1 is composite for k = 1, ..., 5, and Mod64 takes
+        // no modulus above 2^62
+        assert_eq!(
+            try_working_modulus(Mod64::from(1_u64 << 36), Mod64::from((1_u64 << 26) - 5))
+                .unwrap_err(),
+            NttError::Overflow
+        );
+        assert_eq!(
+            try_working_modulus(Mod64::from(1_u64 << 40), Mod64::from(1_u64 << 30)).unwrap_err(),
+            NttError::Overflow
+        );
+    }
+
+    #[test]
+    fn test_in_place() {
+        let n = 1 << 9;
+        let c = Constants::<Fp998244353>::for_size(n);
+        let plan = NttPlan::new(&c, n);
+        let v: Vec<Fp998244353> = (0..2 * n).map(|x| Fp998244353::from(x * x)).collect();
+        let expected = forward(v[n..].to_vec(), &c);
+
+        let mut buf = v.clone();
+        forward_in_place(&mut buf[n..], &c);
+        assert_eq!(buf[n..], expected);
+        assert_eq!(buf[..n], v[..n]);
+        inverse_in_place(&mut buf[n..], &c);
+        assert_eq!(buf, v);
+
+        plan.forward_in_place(&mut buf[n..]);
+        assert_eq!(buf[n..], expected);
+        plan.inverse_in_place(&mut buf[n..]);
+        assert_eq!(buf, v);
+    }
+
+    #[test]
+    fn test_negacyclic() {
+        let n = 1 << 6;
+        let c = Constants::<Fp998244353>::for_size(2 * n);
+        let v: Vec<Fp998244353> = (0..n).map(|x| Fp998244353::from(x * 3 + 1)).collect();
+        assert_eq!(negacyclic_inverse(negacyclic_forward(v.clone(), &c), &c), v);
+
+        // X^(n-1) * X = -1
+        let ZERO = Fp998244353::from(0);
+        let ONE = Fp998244353::from(1);
+        let mut a = vec![ZERO; n];
+        let mut b = vec![ZERO; n];
+        a[n - 1] = ONE;
+        b[1] = ONE;
+        let fa = negacyclic_forward(a, &c);
+        let fb = negacyclic_forward(b, &c);
+        let prod = fa.iter().zip(fb).map(|(&x, y)| x * y).collect();
+        let mut expected = vec![ZERO; n];
+        expected[0] = -ONE;
+        assert_eq!(negacyclic_inverse(prod, &c), expected);
+    }
+
+    fn naive_dft<T: PolynomialFieldElement>(v: &[T], c: &Constants<T>) -> Vec<T> {
+        let ONE = c.reduce(T::from(1));
+        let ZERO = c.reduce(T::from(0));
+        let mut wk = ONE;
+        (0..v.len())
+            .map(|_| {
+                let mut x = ONE;
+                let res = v.iter().fold(ZERO, |acc, &a| {
+                    let term = acc + a * x;
+                    x *= wk;
+                    term
+                });
+                wk *= c.w;
+                res
+            })
+            .collect()
+    }
+
+    fn check_arbitrary_length<T: PolynomialFieldElement + Debug>(c: &Constants<T>, n: usize) {
+        let v: Vec<T> = (0..n).map(|x| c.reduce(T::from(x * x + 1))).collect();
+        let f = forward(v.clone(), c);
+        assert_eq!(f, naive_dft(&v, c));
+        assert_eq!(inverse(f, c), v);
+    }
+
+    #[test]
+    fn test_mixed_radix() {
+        [3, 5, 12, 60, 240].iter().for_each(|&n| {
+            check_arbitrary_length(&Constants::<FpBabyBear>::for_size(n), n);
+        });
+        assert!(forward(vec![], &Constants::<FpBabyBear>::for_size(4)).is_empty());
+    }
+
+    #[test]
+    fn test_bluestein() {
+        [7, 17, 119].iter().for_each(|&n| {
+            check_arbitrary_length(&Constants::<Fp998244353>::for_size(n), n);
+        });
+        let c = working_modulus(Mod64::from(7 * 16), Mod64::from(1_u64 << 20));
+        let c = Constants {
+            N: c.N,
+            w: c.w.mod_exp(Mod64::from(16), c.N),
+        };
+        check_arbitrary_length(&c, 7);
+    }
+
+    #[test]
+    fn test_plan() {
+        let n = 16;
+        let v: Vec<BigInt> = (0..n).map(BigInt::from).collect();
+        let c = working_modulus(BigInt::from(n), BigInt::from(n * n + 1));
+        let plan = NttPlan::new(&c, n);
+        assert_eq!(plan.forward(v.clone()), forward(v.clone(), &c));
+        assert_eq!(plan.inverse(plan.forward(v.clone())), v);
+
+        let half = NttPlan::with_order(&c, n / 2, c.order());
+        let v = v[..n / 2].to_vec();
+        assert_eq!(half.inverse(half.forward(v.clone())), v);
+    }
+
+    #[test]
+    fn test_batch() {
+        let (n, rows) = (32, 50);
+        let c = Constants::<Fp998244353>::for_size(n);
+        let matrix: Vec<Fp998244353> = (0..n * rows)
+            .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>() % 998244353))
+            .collect();
+        let mut batch = matrix.clone();
+        forward_batch(&mut batch, n, &c);
+        batch
+            .chunks(n)
+            .zip(matrix.chunks(n))
+            .for_each(|(row, v)| assert_eq!(row.to_vec(), forward(v.to_vec(), &c)));
+        inverse_batch(&mut batch, n, &c);
+        assert_eq!(batch, matrix);
+
+        let plan = NttPlan::new(&c, n);
+        let mut empty: Vec<Fp998244353> = vec![];
+        plan.forward_batch(&mut empty);
+        let mut ragged = matrix[..n + 3].to_vec();
+        assert_eq!(
+            plan.try_forward_batch(&mut ragged).unwrap_err(),
+            NttError::LengthMismatch {
+                expected: 2 * n,
+                found: n + 3
+            }
+        );
+        assert_eq!(
+            try_inverse_batch(&mut ragged, 5, &c).unwrap_err(),
+            NttError::NonPowerOfTwoLength(5)
+        );
+    }
+
     #[test]
     fn test_roots_of_unity() {
         let N = 10;
@@ -1,6 +1,7 @@
 use std::{
     cmp::Ordering,
-    fmt::Display,
+    fmt::{Debug, Display},
+    marker::PhantomData,
     num::NonZeroU128,
     ops::{
         Add, AddAssign, BitAnd, BitOr, Div, DivAssign, Mul, MulAssign, Neg, Shl, ShlAssign, Shr,
@@ -18,7 +19,7 @@ use crypto_bigint::{
 use itertools::Itertools;
 use rand::{thread_rng, Error, Rng};
 
-use crate::polynomial::PolynomialFieldElement;
+use crate::{error::NttError, ntt::Constants, polynomial::PolynomialFieldElement};
 
 pub enum BigIntType {
     U16(u16),
@@ -29,7 +30,7 @@ pub enum BigIntType {
 
 pub trait NttFieldElement {
     // all operations should be under the modular group `M`
-    fn set_mod(&mut self, M: Self) -> Result<(), String>;
+    fn set_mod(&mut self, M: Self) -> Result<(), NttError>;
     fn rem(&self, M: Self) -> Self;
     fn pow(&self, n: u128) -> Self;
     fn mod_exp(&self, exp: Self, M: Self) -> Self;
@@ -54,14 +55,13 @@ impl BigInt {
                 BigIntType::U32(x) => DynResidue::new(&U256::from(x), params),
                 BigIntType::U64(x) => DynResidue::new(&U256::from(x), params),
                 BigIntType::U128(x) => DynResidue::new(&U256::from(x), params),
-                _ => panic!("received invalid `BigIntType`"),
             },
         }
     }
 
-    pub fn set_mod(&mut self, M: BigInt) -> Result<(), String> {
+    pub fn set_mod(&mut self, M: BigInt) -> Result<(), NttError> {
         if M.is_even() {
-            return Err("modulus must be odd".to_string());
+            return Err(NttError::EvenModulus);
         }
         let params = DynResidueParams::new(&(U256::from(M.v.retrieve())));
         self.v = DynResidue::new(&self.v.retrieve(), params);
@@ -134,19 +134,41 @@ impl BigInt {
         !is_odd
     }
 
-    pub fn to_u32(&self) -> Result<u32, String> {
+    pub fn bits(&self) -> u32 {
+        self.v.retrieve().bits() as u32
+    }
+
+    pub fn to_u64(&self) -> Result<u64, NttError> {
+        let ret = self.v.retrieve().as_words()[0];
+        if BigInt::from(ret) != *self {
+            return Err(NttError::Overflow);
+        }
+        Ok(ret)
+    }
+
+    pub fn to_u128(&self) -> Result<u128, NttError> {
+        let v = self.v.retrieve();
+        let words = v.as_words();
+        let ret = words[0] as u128 | (words[1] as u128) << 64;
+        if BigInt::from(ret) != *self {
+            return Err(NttError::Overflow);
+        }
+        Ok(ret)
+    }
+
+    pub fn to_u32(&self) -> Result<u32, NttError> {
         let ret = self.v.retrieve().as_words()[0] as u32;
         if BigInt::from(ret) != *self {
-            return Err(format!("Overflow error -- {} exceeds u32 size limits", self).to_string());
+            return Err(NttError::Overflow);
         }
         Ok(ret)
     }
 }
 
 impl NttFieldElement for BigInt {
-    fn set_mod(&mut self, M: Self) -> Result<(), String> {
+    fn set_mod(&mut self, M: Self) -> Result<(), NttError> {
         if M.is_even() {
-            return Err("modulus must be odd".to_string());
+            return Err(NttError::EvenModulus);
         }
         let params = DynResidueParams::new(&(U256::from(M.v.retrieve())));
         self.v = DynResidue::new(&self.v.retrieve(), params);
@@ -794,10 +816,659 @@ impl Display for BigInt {
 
 impl PolynomialFieldElement for BigInt {}
 
+// largest modulus for which Barrett products fit in a `u128`
+const MOD64_LIMIT: u64 = 1 << 62;
+
+/// Word-sized residue with a runtime modulus below 2^62, reduced with
+/// Barrett's method. A zero modulus means plain (wrapping) `u64` arithmetic,
+/// which is what values built through `From` start out as; plain values from
+/// 2^63 up are read as negative (two's complement) once reduced.
+#[derive(Debug, Clone, Copy)]
+pub struct Mod64 {
+    pub v: u64,
+    m: u64,
+    mu: u64,
+}
+
+const fn barrett_mu(m: u64) -> u64 {
+    if m >= MOD64_LIMIT {
+        return 0;
+    }
+    let k = 64 - m.leading_zeros();
+    ((1_u128 << (2 * k)) / m as u128) as u64
+}
+
+fn barrett_reduce(x: u128, m: u64, mu: u64) -> u64 {
+    let k = 64 - m.leading_zeros();
+    let q = ((x >> (k - 1)) * mu as u128) >> (k + 1);
+    let mut r = (x - q * m as u128) as u64;
+    while r >= m {
+        r -= m;
+    }
+    r
+}
+
+impl Mod64 {
+    pub fn new(v: u64, M: u64) -> Self {
+        Mod64::try_new(v, M).unwrap()
+    }
+
+    pub fn try_new(v: u64, M: u64) -> Result<Self, NttError> {
+        let mut res = Mod64::from(v);
+        res.set_mod(Mod64::from(M))?;
+        Ok(res)
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.m
+    }
+
+    // picks the modulus of whichever operand has one, preferring `self`
+    fn params(&self, rhs: &Mod64) -> (u64, u64) {
+        if self.m != 0 {
+            (self.m, self.mu)
+        } else {
+            (rhs.m, rhs.mu)
+        }
+    }
+
+    fn reduced(&self, m: u64) -> u64 {
+        if self.m == 0 && self.v >> 63 == 1 {
+            let r = self.v.wrapping_neg() % m;
+            return if r == 0 { 0 } else { m - r };
+        }
+        if self.v < m {
+            self.v
+        } else if self.v - m < m {
+            self.v - m
+        } else {
+            self.v % m
+        }
+    }
+
+    fn with_params(v: u64, (m, mu): (u64, u64)) -> Mod64 {
+        Mod64 { v, m, mu }
+    }
+}
+
+impl NttFieldElement for Mod64 {
+    fn set_mod(&mut self, M: Self) -> Result<(), NttError> {
+        if M.v == 0 || M.v >= MOD64_LIMIT {
+            return Err(NttError::InvalidModulus);
+        }
+        self.v = self.reduced(M.v);
+        self.m = M.v;
+        self.mu = barrett_mu(M.v);
+        Ok(())
+    }
+
+    fn rem(&self, M: Self) -> Self {
+        if self.v < M.v {
+            return *self;
+        }
+        Mod64 {
+            v: self.reduced(M.v),
+            ..*self
+        }
+    }
+
+    fn pow(&self, n: u128) -> Self {
+        if self.m == 0 {
+            let mut res = 1_u64;
+            let mut b = self.v;
+            let mut e = n;
+            while e > 0 {
+                if e & 1 == 1 {
+                    res = res.wrapping_mul(b);
+                }
+                b = b.wrapping_mul(b);
+                e >>= 1;
+            }
+            return Mod64::from(res);
+        }
+        let mut res = Mod64::with_params(1 % self.m, (self.m, self.mu));
+        let mut b = *self;
+        let mut e = n;
+        while e > 0 {
+            if e & 1 == 1 {
+                res *= b;
+            }
+            b *= b;
+            e >>= 1;
+        }
+        res
+    }
+
+    fn mod_exp(&self, exp: Self, M: Self) -> Self {
+        let mut b = *self;
+        b.set_mod(M).unwrap();
+        b.pow(exp.v as u128)
+    }
+
+    fn is_even(&self) -> bool {
+        self.v & 1 == 0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.v == 0
+    }
+
+    fn to_bigint(&self) -> BigInt {
+        BigInt::from(self.v)
+    }
+}
+
+impl From<u16> for Mod64 {
+    fn from(value: u16) -> Self {
+        Mod64::from(value as u64)
+    }
+}
+
+impl From<i32> for Mod64 {
+    fn from(value: i32) -> Self {
+        Mod64::from(value as i64 as u64)
+    }
+}
+
+impl From<usize> for Mod64 {
+    fn from(value: usize) -> Self {
+        Mod64::from(value as u64)
+    }
+}
+
+impl From<u32> for Mod64 {
+    fn from(value: u32) -> Self {
+        Mod64::from(value as u64)
+    }
+}
+
+impl From<u64> for Mod64 {
+    fn from(value: u64) -> Self {
+        Mod64 {
+            v: value,
+            m: 0,
+            mu: 0,
+        }
+    }
+}
+
+impl From<u128> for Mod64 {
+    fn from(value: u128) -> Self {
+        Mod64::from(u64::try_from(value).expect("value exceeds 64 bits"))
+    }
+}
+
+impl Add for Mod64 {
+    type Output = Mod64;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let params = self.params(&rhs);
+        if params.0 == 0 {
+            return Mod64::from(self.v.wrapping_add(rhs.v));
+        }
+        let s = self.reduced(params.0) + rhs.reduced(params.0);
+        Mod64::with_params(if s >= params.0 { s - params.0 } else { s }, params)
+    }
+}
+
+impl AddAssign for Mod64 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl Sub for Mod64 {
+    type Output = Mod64;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let params = self.params(&rhs);
+        if params.0 == 0 {
+            return Mod64::from(self.v.wrapping_sub(rhs.v));
+        }
+        let (a, b) = (self.reduced(params.0), rhs.reduced(params.0));
+        Mod64::with_params(if a < b { params.0 - b + a } else { a - b }, params)
+    }
+}
+
+impl SubAssign for Mod64 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl Neg for Mod64 {
+    type Output = Mod64;
+
+    fn neg(self) -> Self::Output {
+        if self.m == 0 {
+            return Mod64::from(self.v.wrapping_neg());
+        }
+        let v = if self.v == 0 { 0 } else { self.m - self.v };
+        Mod64 { v, ..self }
+    }
+}
+
+impl Mul for Mod64 {
+    type Output = Mod64;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let params = self.params(&rhs);
+        if params.0 == 0 {
+            return Mod64::from(self.v.wrapping_mul(rhs.v));
+        }
+        let x = self.reduced(params.0) as u128 * rhs.reduced(params.0) as u128;
+        Mod64::with_params(barrett_reduce(x, params.0, params.1), params)
+    }
+}
+
+impl MulAssign for Mod64 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl Div for Mod64 {
+    type Output = Mod64;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Mod64 {
+            v: self.v / rhs.v,
+            ..self
+        }
+    }
+}
+
+impl DivAssign for Mod64 {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl Invert for Mod64 {
+    type Output = Mod64;
+
+    fn invert(&self) -> Self::Output {
+        // without a modulus there is nothing to invert in
+        if self.m == 0 {
+            return Mod64 { v: 0, ..*self };
+        }
+        // extended euclid, since the modulus is not required to be prime
+        let (mut r0, mut r1) = (self.m as i128, self.v as i128);
+        let (mut t0, mut t1) = (0_i128, 1_i128);
+        while r1 != 0 {
+            let q = r0 / r1;
+            (r0, r1) = (r1, r0 - q * r1);
+            (t0, t1) = (t1, t0 - q * t1);
+        }
+        if r0 != 1 {
+            return Mod64 { v: 0, ..*self };
+        }
+        let v = t0.rem_euclid(self.m as i128) as u64;
+        Mod64 { v, ..*self }
+    }
+}
+
+impl Shr<usize> for Mod64 {
+    type Output = Mod64;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        Mod64 {
+            v: self.v >> rhs,
+            ..self
+        }
+    }
+}
+
+impl ShrAssign<usize> for Mod64 {
+    fn shr_assign(&mut self, rhs: usize) {
+        *self = *self >> rhs;
+    }
+}
+
+impl PartialEq for Mod64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.v == other.v
+    }
+}
+
+impl Eq for Mod64 {}
+
+impl PartialOrd for Mod64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Mod64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.v.cmp(&other.v)
+    }
+}
+
+impl Display for Mod64 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.v)
+    }
+}
+
+impl PolynomialFieldElement for Mod64 {}
+
+/// An NTT-friendly prime with its multiplicative generator and a primitive
+/// `2^TWO_ADICITY`-th root of unity.
+pub trait NttPrime: Debug + Clone + Copy + Send + Sync + 'static {
+    const MODULUS: u64;
+    const GENERATOR: u64;
+    const TWO_ADICITY: u32;
+    const ROOT: u64;
+    const MU: u64 = barrett_mu(Self::MODULUS);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct P998244353;
+
+impl NttPrime for P998244353 {
+    const MODULUS: u64 = 998244353;
+    const GENERATOR: u64 = 3;
+    const TWO_ADICITY: u32 = 23;
+    const ROOT: u64 = 15311432;
+}
+
+/// 2^64 - 2^32 + 1
+#[derive(Debug, Clone, Copy)]
+pub struct Goldilocks;
+
+impl NttPrime for Goldilocks {
+    const MODULUS: u64 = 18446744069414584321;
+    const GENERATOR: u64 = 7;
+    const TWO_ADICITY: u32 = 32;
+    const ROOT: u64 = 1753635133440165772;
+}
+
+/// 2^31 - 2^27 + 1
+#[derive(Debug, Clone, Copy)]
+pub struct BabyBear;
+
+impl NttPrime for BabyBear {
+    const MODULUS: u64 = 2013265921;
+    const GENERATOR: u64 = 31;
+    const TWO_ADICITY: u32 = 27;
+    const ROOT: u64 = 440564289;
+}
+
+/// 2^32 - 2^20 + 1
+#[derive(Debug, Clone, Copy)]
+pub struct P4293918721;
+
+impl NttPrime for P4293918721 {
+    const MODULUS: u64 = 4293918721;
+    const GENERATOR: u64 = 19;
+    const TWO_ADICITY: u32 = 20;
+    const ROOT: u64 = 3156611342;
+}
+
+/// Residue modulo the compile-time prime `P::MODULUS`. Results of arithmetic
+/// are always reduced, but `Fp::raw` can hold the modulus itself so that it
+/// can serve as `Constants::N`.
+#[derive(Debug, Clone, Copy)]
+pub struct Fp<P: NttPrime> {
+    pub v: u64,
+    _p: PhantomData<P>,
+}
+
+pub type Fp998244353 = Fp<P998244353>;
+pub type FpGoldilocks = Fp<Goldilocks>;
+pub type FpBabyBear = Fp<BabyBear>;
+pub type Fp4293918721 = Fp<P4293918721>;
+
+impl<P: NttPrime> Fp<P> {
+    pub fn raw(v: u64) -> Self {
+        Fp { v, _p: PhantomData }
+    }
+
+    pub fn modulus() -> Self {
+        Fp::raw(P::MODULUS)
+    }
+
+    fn canonical(&self) -> u64 {
+        if self.v < P::MODULUS {
+            self.v
+        } else {
+            self.v % P::MODULUS
+        }
+    }
+
+    fn reduce(x: u128) -> u64 {
+        if P::MODULUS < MOD64_LIMIT {
+            barrett_reduce(x, P::MODULUS, P::MU)
+        } else {
+            (x % P::MODULUS as u128) as u64
+        }
+    }
+}
+
+impl<P: NttPrime> NttFieldElement for Fp<P> {
+    fn set_mod(&mut self, M: Self) -> Result<(), NttError> {
+        if M.v != P::MODULUS {
+            return Err(NttError::InvalidModulus);
+        }
+        self.v = self.canonical();
+        Ok(())
+    }
+
+    fn rem(&self, M: Self) -> Self {
+        if self.v < M.v {
+            return *self;
+        }
+        Fp::raw(self.v % M.v)
+    }
+
+    fn pow(&self, n: u128) -> Self {
+        let mut res = Fp::from(1_u64);
+        let mut b = *self;
+        let mut e = n;
+        while e > 0 {
+            if e & 1 == 1 {
+                res *= b;
+            }
+            b *= b;
+            e >>= 1;
+        }
+        res
+    }
+
+    fn mod_exp(&self, exp: Self, _M: Self) -> Self {
+        self.pow(exp.v as u128)
+    }
+
+    fn is_even(&self) -> bool {
+        self.v & 1 == 0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.canonical() == 0
+    }
+
+    fn to_bigint(&self) -> BigInt {
+        BigInt::from(self.v)
+    }
+}
+
+impl<P: NttPrime> From<u16> for Fp<P> {
+    fn from(value: u16) -> Self {
+        Fp::from(value as u64)
+    }
+}
+
+impl<P: NttPrime> From<i32> for Fp<P> {
+    fn from(value: i32) -> Self {
+        if value < 0 {
+            return -Fp::from(value.unsigned_abs() as u64);
+        }
+        Fp::from(value as u64)
+    }
+}
+
+impl<P: NttPrime> From<usize> for Fp<P> {
+    fn from(value: usize) -> Self {
+        Fp::from(value as u64)
+    }
+}
+
+impl<P: NttPrime> From<u32> for Fp<P> {
+    fn from(value: u32) -> Self {
+        Fp::from(value as u64)
+    }
+}
+
+impl<P: NttPrime> From<u64> for Fp<P> {
+    fn from(value: u64) -> Self {
+        Fp::raw(value % P::MODULUS)
+    }
+}
+
+impl<P: NttPrime> From<u128> for Fp<P> {
+    fn from(value: u128) -> Self {
+        Fp::raw((value % P::MODULUS as u128) as u64)
+    }
+}
+
+impl<P: NttPrime> Add for Fp<P> {
+    type Output = Fp<P>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let s = self.canonical() as u128 + rhs.canonical() as u128;
+        let s = if s >= P::MODULUS as u128 {
+            s - P::MODULUS as u128
+        } else {
+            s
+        };
+        Fp::raw(s as u64)
+    }
+}
+
+impl<P: NttPrime> AddAssign for Fp<P> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl<P: NttPrime> Sub for Fp<P> {
+    type Output = Fp<P>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let (a, b) = (self.canonical(), rhs.canonical());
+        Fp::raw(if a < b { P::MODULUS - b + a } else { a - b })
+    }
+}
+
+impl<P: NttPrime> SubAssign for Fp<P> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl<P: NttPrime> Neg for Fp<P> {
+    type Output = Fp<P>;
+
+    fn neg(self) -> Self::Output {
+        Fp::raw(0) - self
+    }
+}
+
+impl<P: NttPrime> Mul for Fp<P> {
+    type Output = Fp<P>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Fp::raw(Fp::<P>::reduce(
+            self.canonical() as u128 * rhs.canonical() as u128,
+        ))
+    }
+}
+
+impl<P: NttPrime> MulAssign for Fp<P> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl<P: NttPrime> Div for Fp<P> {
+    type Output = Fp<P>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Fp::raw(self.v / rhs.v)
+    }
+}
+
+impl<P: NttPrime> DivAssign for Fp<P> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<P: NttPrime> Invert for Fp<P> {
+    type Output = Fp<P>;
+
+    fn invert(&self) -> Self::Output {
+        self.pow((P::MODULUS - 2) as u128)
+    }
+}
+
+impl<P: NttPrime> Shr<usize> for Fp<P> {
+    type Output = Fp<P>;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        Fp::raw(self.v >> rhs)
+    }
+}
+
+impl<P: NttPrime> ShrAssign<usize> for Fp<P> {
+    fn shr_assign(&mut self, rhs: usize) {
+        *self = *self >> rhs;
+    }
+}
+
+impl<P: NttPrime> PartialEq for Fp<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.v == other.v
+    }
+}
+
+impl<P: NttPrime> Eq for Fp<P> {}
+
+impl<P: NttPrime> PartialOrd for Fp<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: NttPrime> Ord for Fp<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.v.cmp(&other.v)
+    }
+}
+
+impl<P: NttPrime> Display for Fp<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.v)
+    }
+}
+
+impl<P: NttPrime> PolynomialFieldElement for Fp<P> {
+    fn field_constants() -> Option<Constants<Self>> {
+        Some(Constants::for_size(1 << P::TWO_ADICITY))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::numbers::BigInt;
+    use crate::error::NttError;
+    use crate::numbers::{
+        BabyBear, BigInt, Fp, FpGoldilocks, Goldilocks, Mod64, NttFieldElement, NttPrime,
+        P4293918721, P998244353,
+    };
+    use crypto_bigint::Invert;
     use mod_exp::mod_exp;
+    use rand::Rng;
 
     #[test]
     fn test_mod_exp() {
@@ -849,4 +1520,96 @@ mod tests {
         let a = BigInt::from(1);
         println!("{}", a >> 1);
     }
+
+    #[test]
+    fn test_mod64_arithmetic() {
+        let p: u64 = 2305843009213693951; // 2^61 - 1
+        (0..100).for_each(|_| {
+            let x = rand::thread_rng().gen::<u64>() % p;
+            let y = rand::thread_rng().gen::<u64>() % p;
+            let a = Mod64::new(x, p);
+            let b = Mod64::new(y, p);
+            assert_eq!((a * b).v as u128, x as u128 * y as u128 % p as u128);
+            assert_eq!((a + b).v, (x + y) % p);
+            assert_eq!((a - b).v, (x + p - y) % p);
+            if x != 0 {
+                assert_eq!((a * a.invert()).v, 1);
+            }
+        });
+
+        let mut neg = Mod64::from(-3);
+        neg.set_mod(Mod64::from(7)).unwrap();
+        assert_eq!(neg.v, 4);
+        assert_eq!((Mod64::from(-5) + Mod64::new(2, 7)).v, 4);
+        assert_eq!(Mod64::from(-14).rem(Mod64::from(7)).v, 0);
+        assert_eq!(Mod64::from(3).invert().v, 0);
+    }
+
+    #[test]
+    fn test_set_mod_errors() {
+        assert_eq!(
+            Mod64::try_new(3, 1 << 62).unwrap_err(),
+            NttError::InvalidModulus
+        );
+        let mut x = BigInt::from(3);
+        assert_eq!(x.set_mod(BigInt::from(10)), Err(NttError::EvenModulus));
+        assert_eq!(BigInt::from(1_u64 << 40).to_u32(), Err(NttError::Overflow));
+        assert_eq!(BigInt::from(u128::MAX).to_u128(), Ok(u128::MAX));
+        assert_eq!((BigInt::from(1) << 130).to_u128(), Err(NttError::Overflow));
+    }
+
+    #[test]
+    fn test_mod64_mod_exp() {
+        let N = 73;
+        (2..10).for_each(|x| {
+            (2..10).for_each(|y| {
+                assert_eq!(
+                    Mod64::from(mod_exp(x, y, N)),
+                    Mod64::from(x).mod_exp(Mod64::from(y), Mod64::from(N))
+                );
+            })
+        });
+    }
+
+    fn check_root<P: NttPrime>() {
+        let ONE = Fp::<P>::from(1);
+        let root = Fp::<P>::from(P::ROOT);
+        assert_eq!(root.pow(1 << P::TWO_ADICITY), ONE);
+        assert!(root.pow(1 << (P::TWO_ADICITY - 1)) != ONE);
+        assert_eq!(
+            Fp::<P>::from(P::GENERATOR).pow(((P::MODULUS - 1) >> P::TWO_ADICITY) as u128),
+            root
+        );
+    }
+
+    #[test]
+    fn test_fixed_roots() {
+        check_root::<P998244353>();
+        check_root::<Goldilocks>();
+        check_root::<BabyBear>();
+        check_root::<P4293918721>();
+    }
+
+    #[test]
+    fn test_goldilocks_arithmetic() {
+        let p = Goldilocks::MODULUS as u128;
+        (0..100).for_each(|_| {
+            let x = rand::thread_rng().gen::<u64>() % Goldilocks::MODULUS;
+            let y = rand::thread_rng().gen::<u64>() % Goldilocks::MODULUS;
+            let (a, b) = (FpGoldilocks::from(x), FpGoldilocks::from(y));
+            assert_eq!((a * b).v as u128, x as u128 * y as u128 % p);
+            assert_eq!((a + b).v as u128, (x as u128 + y as u128) % p);
+            assert_eq!((a - b).v as u128, (x as u128 + p - y as u128) % p);
+            if x != 0 {
+                assert_eq!((a * a.invert()).v, 1);
+            }
+        });
+
+        let mut neg = Mod64::from(-3);
+        neg.set_mod(Mod64::from(7)).unwrap();
+        assert_eq!(neg.v, 4);
+        assert_eq!((Mod64::from(-5) + Mod64::new(2, 7)).v, 4);
+        assert_eq!(Mod64::from(-14).rem(Mod64::from(7)).v, 0);
+        assert_eq!(Mod64::from(3).invert().v, 0);
+    }
 }
@@ -1,17 +1,22 @@
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
-};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use rayon::prelude::*;
 use std;
 use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
     fmt::Display,
-    ops::{Add, AddAssign, Div, Index, Mul, MulAssign, Neg, ShrAssign, Sub},
+    ops::{Add, AddAssign, Div, Index, Mul, MulAssign, Neg, Rem, ShrAssign, Sub, SubAssign},
 };
 
 use crypto_bigint::Invert;
 use itertools::{EitherOrBoth::*, Itertools};
 
-use crate::{ntt::*, numbers::NttFieldElement};
+use crate::{
+    error::NttError,
+    ntt::*,
+    numbers::{BigInt, NttFieldElement},
+};
 
 pub trait PolynomialFieldElement:
     NttFieldElement
@@ -38,6 +43,12 @@ pub trait PolynomialFieldElement:
     + Send
     + Sync
 {
+    /// Constants of the field the type itself works in, for the operators
+    /// on `Polynomial` that need a transform; `None` when the modulus is
+    /// only known at runtime.
+    fn field_constants() -> Option<Constants<Self>> {
+        None
+    }
 }
 
 pub trait PolynomialTrait<T: PolynomialFieldElement> {
@@ -47,8 +58,30 @@ pub trait PolynomialTrait<T: PolynomialFieldElement> {
     fn to_vec(&self) -> Vec<T>;
     fn set_coef(&mut self, a: T, idx: usize);
     fn set_vec(&mut self, v: Vec<T>);
+
+    fn try_degree(&self) -> Result<usize, NttError> {
+        let ZERO = T::from(0);
+        let v = self.to_vec();
+        let start = v
+            .iter()
+            .position(|&x| x != ZERO)
+            .ok_or(NttError::ZeroPolynomial)?;
+        Ok(v.len() - start - 1)
+    }
+}
+
+/// Order in which a coefficient vector lists the terms of a polynomial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoefficientOrder {
+    /// Constant term first, as in most other libraries and `concrete-ntt`.
+    Ascending,
+    /// Leading term first, the layout `Polynomial::coef` uses.
+    Descending,
 }
 
+/// Coefficients are stored highest degree first in `coef`, which `new`
+/// and `Index` use as is; `from_ascending`, `coeff` and `to_vec_in` give
+/// an order-explicit view for interop.
 #[derive(Debug, Clone)]
 pub struct Polynomial<T: PolynomialFieldElement> {
     pub coef: Vec<T>,
@@ -60,15 +93,14 @@ impl<T: PolynomialFieldElement> PolynomialTrait<T> for Polynomial<T> {
     }
 
     fn degree(&self) -> usize {
-        let ZERO = T::from(0);
-        let start = self.coef.iter().position(|&x| x != ZERO).unwrap();
-        self.len() - start - 1
+        self.try_degree().unwrap()
     }
 
+    // zero for the empty polynomial, like for any other zero polynomial
     fn max(&self) -> T {
-        let mut ans = self.coef[0];
+        let mut ans = T::from(0);
 
-        self.coef[1..].iter().for_each(|&x| {
+        self.coef.iter().for_each(|&x| {
             if ans < x {
                 ans = x;
             }
@@ -94,6 +126,74 @@ impl<T: PolynomialFieldElement> Polynomial<T> {
     pub fn new(coef: Vec<T>) -> Self {
         Polynomial { coef }
     }
+
+    pub fn from_descending(coef: Vec<T>) -> Self {
+        Polynomial { coef }
+    }
+
+    pub fn from_ascending(mut coef: Vec<T>) -> Self {
+        coef.reverse();
+        Polynomial { coef }
+    }
+
+    pub fn with_order(coef: Vec<T>, order: CoefficientOrder) -> Self {
+        match order {
+            CoefficientOrder::Ascending => Polynomial::from_ascending(coef),
+            CoefficientOrder::Descending => Polynomial::from_descending(coef),
+        }
+    }
+
+    /// Coefficient of `x^i`, zero beyond the stored terms.
+    pub fn coeff(&self, i: usize) -> T {
+        if i < self.coef.len() {
+            self.coef[self.coef.len() - 1 - i]
+        } else {
+            T::from(0)
+        }
+    }
+
+    /// Sets the coefficient of `x^i`, growing the polynomial as needed.
+    pub fn set_coeff(&mut self, i: usize, x: T) {
+        if i >= self.coef.len() {
+            let pad = vec![T::from(0); i + 1 - self.coef.len()];
+            self.coef.splice(0..0, pad);
+        }
+        let n = self.coef.len();
+        self.coef[n - 1 - i] = x;
+    }
+
+    pub fn to_ascending(&self) -> Vec<T> {
+        self.coef.iter().rev().cloned().collect()
+    }
+
+    pub fn to_descending(&self) -> Vec<T> {
+        self.coef.clone()
+    }
+
+    pub fn to_vec_in(&self, order: CoefficientOrder) -> Vec<T> {
+        match order {
+            CoefficientOrder::Ascending => self.to_ascending(),
+            CoefficientOrder::Descending => self.to_descending(),
+        }
+    }
+
+    /// Horner evaluation at `x`, in the coefficients' own field.
+    pub fn evaluate(&self, x: T) -> T {
+        let ZERO = T::from(0);
+        self.coef.iter().fold(ZERO, |acc, &a| acc * x + a)
+    }
+
+    /// `self / rhs`, failing on a zero divisor where the `/` operator
+    /// panics.
+    pub fn try_div(self, rhs: Polynomial<T>) -> Result<Polynomial<T>, NttError> {
+        Ok(operator_div_rem(self, rhs)?.0)
+    }
+
+    /// `self % rhs`, failing on a zero divisor where the `%` operator
+    /// panics.
+    pub fn try_rem(self, rhs: Polynomial<T>) -> Result<Polynomial<T>, NttError> {
+        Ok(operator_div_rem(self, rhs)?.1)
+    }
 }
 
 pub fn mul_brute<T: PolynomialFieldElement>(
@@ -116,202 +216,2161 @@ pub fn mul_brute<T: PolynomialFieldElement>(
     Polynomial { coef: out }
 }
 
+/// Karatsuba multiplication in the coefficients' own arithmetic, like
+/// `mul_brute`, but with exactly `lhs.len() + rhs.len() - 1` terms.
+pub fn mul_karatsuba<T: PolynomialFieldElement>(
+    lhs: Polynomial<T>,
+    rhs: Polynomial<T>,
+) -> Polynomial<T> {
+    if lhs.coef.is_empty() || rhs.coef.is_empty() {
+        return Polynomial { coef: vec![] };
+    }
+    Polynomial {
+        coef: karatsuba(&lhs.coef, &rhs.coef, T::from(0), KARATSUBA_THRESHOLD),
+    }
+}
+
+// direct `a * b` of non-empty slices, in the arithmetic of `zero`
+fn schoolbook<T: PolynomialFieldElement>(a: &[T], b: &[T], zero: T) -> Vec<T> {
+    let mut out = vec![zero; a.len() + b.len() - 1];
+    a.iter().enumerate().for_each(|(i, &x)| {
+        b.iter().enumerate().for_each(|(j, &y)| out[i + j] += x * y);
+    });
+    out
+}
+
+// `a + b`, zero-extending the shorter slice
+fn add_slices<T: PolynomialFieldElement>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter()
+        .zip_longest(b.iter())
+        .map(|p| match p {
+            Both(&x, &y) => x + y,
+            Left(&x) | Right(&x) => x,
+        })
+        .collect()
+}
+
+// Karatsuba's `a * b` of non-empty slices, down to schoolbook at
+// `threshold` terms; a much longer operand is cut into blocks the length
+// of the shorter one so that each split stays balanced
+fn karatsuba<T: PolynomialFieldElement>(a: &[T], b: &[T], zero: T, threshold: usize) -> Vec<T> {
+    if a.len() > b.len() {
+        return karatsuba(b, a, zero, threshold);
+    }
+    let (n, m) = (a.len(), b.len());
+    if n <= threshold.max(1) {
+        return schoolbook(a, b, zero);
+    }
+    let mut out = vec![zero; n + m - 1];
+    if m >= 2 * n {
+        b.chunks(n).enumerate().for_each(|(i, block)| {
+            karatsuba(a, block, zero, threshold)
+                .into_iter()
+                .enumerate()
+                .for_each(|(j, x)| out[i * n + j] += x);
+        });
+        return out;
+    }
+
+    // a = a0 + x^h a1, b = b0 + x^h b1
+    let h = n / 2;
+    let (a0, a1) = a.split_at(h);
+    let (b0, b1) = b.split_at(h);
+    let z0 = karatsuba(a0, b0, zero, threshold);
+    let z2 = karatsuba(a1, b1, zero, threshold);
+    let z1 = karatsuba(&add_slices(a0, a1), &add_slices(b0, b1), zero, threshold);
+    z1.iter().enumerate().for_each(|(i, &x)| out[h + i] += x);
+    z0.iter().enumerate().for_each(|(i, &x)| {
+        out[i] += x;
+        out[h + i] = out[h + i] - x;
+    });
+    z2.iter().enumerate().for_each(|(i, &x)| {
+        out[2 * h + i] += x;
+        out[h + i] = out[h + i] - x;
+    });
+    out
+}
+
 #[cfg(feature = "parallel")]
 pub fn fast_mul<T: PolynomialFieldElement>(
     lhs: impl PolynomialTrait<T>,
     rhs: impl PolynomialTrait<T>,
     c: &Constants<T>,
 ) -> Polynomial<T> {
-    let v1_deg = lhs.degree();
-    let v2_deg = rhs.degree();
-    let n = (lhs.len() + rhs.len()).next_power_of_two();
-    let ZERO = T::from(0);
-
-    let v1: Vec<T> = vec![ZERO; n - lhs.len()]
-        .into_iter()
-        .chain(lhs.to_vec().into_iter())
-        .collect();
-    let v2: Vec<T> = vec![ZERO; n - rhs.len()]
-        .into_iter()
-        .chain(rhs.to_vec().into_iter())
-        .collect();
-
-    let a_forward = forward(v1, &c);
-    let b_forward = forward(v2, &c);
+    try_fast_mul(lhs, rhs, c).unwrap()
+}
 
-    let mut mul = vec![ZERO; n as usize];
-    mul.par_iter_mut()
-        .enumerate()
-        .for_each(|(i, x)| *x = (a_forward[i] * b_forward[i]).rem(c.N));
+#[cfg(not(feature = "parallel"))]
+pub fn fast_mul<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
+    lhs: P,
+    rhs: P,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_fast_mul(lhs, rhs, c).unwrap()
+}
 
-    let coef = inverse(mul, &c);
-    // n - polynomial degree - 1
-    let start = n - (v1_deg + v2_deg + 1) - 1;
-    Polynomial {
-        coef: coef[start..=(start + v1_deg + v2_deg)].to_vec(),
+#[cfg(feature = "parallel")]
+pub fn try_fast_mul<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    if is_unbalanced(lhs.len(), rhs.len()) {
+        return try_mul_unbalanced(
+            &Polynomial::new(lhs.to_vec()),
+            &Polynomial::new(rhs.to_vec()),
+            c,
+        );
     }
+    let n = (lhs.len() + rhs.len()).next_power_of_two();
+    try_fast_mul_with_plan(lhs, rhs, &NttPlan::try_new(c, n)?)
 }
 
 #[cfg(not(feature = "parallel"))]
-pub fn fast_mul<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
+pub fn try_fast_mul<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
     lhs: P,
     rhs: P,
     c: &Constants<T>,
-) -> Polynomial<T> {
-    let v1_deg = lhs.degree();
-    let v2_deg = rhs.degree();
+) -> Result<Polynomial<T>, NttError> {
+    if is_unbalanced(lhs.len(), rhs.len()) {
+        return try_mul_unbalanced(
+            &Polynomial::new(lhs.to_vec()),
+            &Polynomial::new(rhs.to_vec()),
+            c,
+        );
+    }
     let n = (lhs.len() + rhs.len()).next_power_of_two();
+    try_fast_mul_with_plan(lhs, rhs, &NttPlan::try_new(c, n)?)
+}
+
+#[cfg(feature = "parallel")]
+fn pointwise<T: PolynomialFieldElement>(a: &[T], b: &[T], N: T) -> Vec<T> {
+    a.par_iter()
+        .zip(b.par_iter())
+        .map(|(&x, &y)| (x * y).rem(N))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn pointwise<T: PolynomialFieldElement>(a: &[T], b: &[T], N: T) -> Vec<T> {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| (x * y).rem(N))
+        .collect()
+}
+
+pub fn fast_mul_with_plan<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    plan: &NttPlan<T>,
+) -> Polynomial<T> {
+    try_fast_mul_with_plan(lhs, rhs, plan).unwrap()
+}
+
+pub fn try_fast_mul_with_plan<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    plan: &NttPlan<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let n = plan.n;
+    if lhs.len() + rhs.len() > n {
+        return Err(NttError::LengthMismatch {
+            expected: n,
+            found: lhs.len() + rhs.len(),
+        });
+    }
+    let (v1_deg, v2_deg) = match (lhs.try_degree(), rhs.try_degree()) {
+        (Ok(x), Ok(y)) => (x, y),
+        _ => return Ok(Polynomial::new(vec![plan.c.reduce(T::from(0))])),
+    };
     let ZERO = T::from(0_u32);
 
     let v1: Vec<T> = vec![ZERO; n - lhs.len()]
         .into_iter()
-        .chain(lhs.to_vec().into_iter())
+        .chain(lhs.to_vec())
         .collect();
     let v2: Vec<T> = vec![ZERO; n - rhs.len()]
         .into_iter()
-        .chain(rhs.to_vec().into_iter())
+        .chain(rhs.to_vec())
         .collect();
 
-    let a_forward = forward(v1, &c);
-    let b_forward = forward(v2, &c);
-
-    let mut mul = vec![ZERO; n as usize];
-    mul.iter_mut()
-        .enumerate()
-        .for_each(|(i, x)| *x = (a_forward[i] * b_forward[i]).rem(c.N));
+    let a_forward = plan.forward(v1);
+    let b_forward = plan.forward(v2);
+    let mul = pointwise(&a_forward, &b_forward, plan.c.N);
 
-    let coef = inverse(mul, &c);
+    let coef = plan.inverse(mul);
     // n - polynomial degree - 1
     let start = n - (v1_deg + v2_deg + 1) - 1;
-    let res = Polynomial {
+    Ok(Polynomial {
         coef: coef[start..=(start + v1_deg + v2_deg)].to_vec(),
-    };
+    })
+}
+
+/// `lhs * rhs` mod `c.N` by overlap-add, for operands of very different
+/// lengths: the longer one is cut into blocks that fit a transform sized
+/// for the shorter one, whose transform is computed once and reused, and
+/// the block products are summed at their offsets. `c.w` needs a
+/// power-of-two order of only about twice the shorter length. `fast_mul`
+/// and `mul_auto` switch to this once one operand is four times the other.
+pub fn mul_unbalanced<T: PolynomialFieldElement>(
+    lhs: &Polynomial<T>,
+    rhs: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_mul_unbalanced(lhs, rhs, c).unwrap()
+}
+
+pub fn try_mul_unbalanced<T: PolynomialFieldElement>(
+    lhs: &Polynomial<T>,
+    rhs: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    if lhs.try_degree().is_err() || rhs.try_degree().is_err() {
+        return Ok(Polynomial::new(vec![c.reduce(T::from(0))]));
+    }
+    let coef = try_overlap_add(&trim(lhs.to_vec()), &trim(rhs.to_vec()), c)?;
+    Ok(Polynomial { coef })
+}
+
+// whether the longer operand is long enough to go block by block
+fn is_unbalanced(x: usize, y: usize) -> bool {
+    x.min(y) * UNBALANCED_RATIO <= x.max(y)
+}
+
+// `block * short` through `plan`, given the transform `fs` of `short`
+fn block_product<T: PolynomialFieldElement>(
+    block: &[T],
+    fs: &[T],
+    short: usize,
+    plan: &NttPlan<T>,
+) -> Vec<T> {
+    let mut fb = block.to_vec();
+    fb.resize(plan.n, T::from(0));
+    plan.forward_in_place(&mut fb);
+    let mut res = plan.inverse(pointwise(&fb, fs, plan.c.N));
+    res.truncate(block.len() + short - 1);
     res
 }
 
-pub fn diff<T: PolynomialFieldElement, P: PolynomialTrait<T>>(mut poly: P) -> P {
-    let N = poly.len();
-    let _poly = poly.to_vec();
-    for n in (1..N).rev() {
-        poly.set_coef(_poly[n - 1] * T::from(N - n), n);
+#[cfg(feature = "parallel")]
+fn block_products<T: PolynomialFieldElement>(
+    long: &[T],
+    block: usize,
+    fs: &[T],
+    short: usize,
+    plan: &NttPlan<T>,
+) -> Vec<Vec<T>> {
+    long.par_chunks(block)
+        .map(|b| block_product(b, fs, short, plan))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn block_products<T: PolynomialFieldElement>(
+    long: &[T],
+    block: usize,
+    fs: &[T],
+    short: usize,
+    plan: &NttPlan<T>,
+) -> Vec<Vec<T>> {
+    long.chunks(block)
+        .map(|b| block_product(b, fs, short, plan))
+        .collect()
+}
+
+// lifted linear convolution `a * b`, one transform of the shorter operand
+// and one forward and inverse transform per block of the longer
+fn try_overlap_add<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if short.is_empty() {
+        return Ok(vec![]);
     }
-    let ZERO = T::from(0);
-    poly.set_coef(ZERO, 0);
-    let mut _poly = poly.to_vec();
-    let start = _poly.iter().position(|&x| x != ZERO).unwrap();
-    _poly = _poly[start..].to_vec();
-    poly.set_vec(_poly);
-    poly
+    let len = a.len() + b.len() - 1;
+    let n = (2 * short.len())
+        .next_power_of_two()
+        .max(MIN_BLOCK_TRANSFORM)
+        .min(len.next_power_of_two());
+    let plan = NttPlan::try_new(c, n)?;
+    // each block product has at most `n` terms
+    let block = n - short.len() + 1;
+
+    let long: Vec<T> = long.iter().map(|&x| c.reduce(x)).collect();
+    let mut fs: Vec<T> = short.iter().map(|&x| c.reduce(x)).collect();
+    fs.resize(n, T::from(0));
+    plan.forward_in_place(&mut fs);
+
+    let mut out = vec![c.reduce(T::from(0)); len];
+    block_products(&long, block, &fs, short.len(), &plan)
+        .into_iter()
+        .enumerate()
+        .for_each(|(i, prod)| {
+            prod.into_iter()
+                .enumerate()
+                .for_each(|(j, x)| out[i * block + j] = (out[i * block + j] + x).rem(c.N));
+        });
+    Ok(out)
 }
 
-impl<T: PolynomialFieldElement> Add<Polynomial<T>> for Polynomial<T> {
-    type Output = Polynomial<T>;
+thread_local! {
+    // per element type, the `(order, bound, Constants)` last chosen by
+    // `multiply`
+    static MODULUS_CACHE: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
 
-    fn add(self, rhs: Polynomial<T>) -> Self::Output {
-        Polynomial {
-            coef: self
-                .coef
-                .iter()
-                .rev()
-                .zip_longest(rhs.coef.iter().rev())
-                .map(|p| match p {
-                    Both(&a, &b) => a + b,
-                    Left(&a) => a,
-                    Right(&b) => b,
-                })
-                .rev()
-                .collect(),
-        }
+// `a * b`, failing where the product wraps around in `T`
+pub(crate) fn checked_mul<T: PolynomialFieldElement>(a: T, b: T) -> Result<T, NttError> {
+    let p = a * b;
+    if !a.is_zero() && p / a != b {
+        return Err(NttError::Overflow);
     }
+    Ok(p)
 }
 
-impl<T: PolynomialFieldElement> Sub<Polynomial<T>> for Polynomial<T> {
-    type Output = Polynomial<T>;
+// a prime above `bound` with a root of unity of order `n`, reusing the
+// cached one when it is large enough
+fn cached_modulus<T: PolynomialFieldElement + 'static>(
+    n: usize,
+    bound: T,
+) -> Result<Constants<T>, NttError> {
+    MODULUS_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let (n, bound) = match cache
+            .get(&TypeId::of::<T>())
+            .and_then(|x| x.downcast_ref::<(usize, T, Constants<T>)>())
+        {
+            Some((order, max, c)) if *order >= n && *max >= bound => return Ok(c.clone()),
+            Some((order, max, _)) => (n.max(*order), if *max > bound { *max } else { bound }),
+            None => (n, bound),
+        };
 
-    fn sub(self, rhs: Polynomial<T>) -> Self::Output {
-        self + (-rhs)
+        // the search starts at `bound * n + 1` and in practice ends well
+        // before twice that, so the type must accept moduli up to there
+        let start = checked_mul(bound, T::from(n))?;
+        let mut probe = T::from(1);
+        probe.set_mod(checked_mul(start, T::from(2))? + T::from(1))?;
+
+        let c = try_working_modulus(T::from(n), bound)?;
+        cache.insert(TypeId::of::<T>(), Box::new((n, bound, c.clone())));
+        Ok(c)
+    })
+}
+
+/// Product of `lhs` and `rhs`, read as polynomials with non-negative integer
+/// coefficients, modulo a prime chosen (and cached per element type) to
+/// exceed `max(lhs) * max(rhs) * min(lhs.len(), rhs.len())`. That bounds
+/// every coefficient of the integer product, so the result is always exact;
+/// when the bound or its modulus does not fit in `T`, or `T` has a fixed
+/// modulus, `try_multiply` returns an error instead.
+pub fn multiply<T: PolynomialFieldElement + 'static>(
+    lhs: &Polynomial<T>,
+    rhs: &Polynomial<T>,
+) -> Polynomial<T> {
+    try_multiply(lhs, rhs).unwrap()
+}
+
+pub fn try_multiply<T: PolynomialFieldElement + 'static>(
+    lhs: &Polynomial<T>,
+    rhs: &Polynomial<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let ZERO = T::from(0);
+    if lhs.coef.is_empty() || rhs.coef.is_empty() {
+        return Ok(Polynomial::new(vec![ZERO]));
     }
+    let (a, b) = (lhs.max(), rhs.max());
+    if a == ZERO || b == ZERO {
+        return Ok(Polynomial::new(vec![ZERO]));
+    }
+    let bound = checked_mul(checked_mul(a, b)?, T::from(lhs.len().min(rhs.len())))?;
+    let n = (lhs.len() + rhs.len()).next_power_of_two();
+    try_fast_mul(lhs.clone(), rhs.clone(), &cached_modulus(n, bound)?)
 }
 
-impl<T: PolynomialFieldElement> Neg for Polynomial<T> {
-    type Output = Polynomial<T>;
+/// Element of `Z_N[X]/(X^n + 1)` with `n` a power of two, coefficients
+/// highest degree first like `Polynomial`. `c.w` must have a power-of-two
+/// order divisible by `2n`. Multiplying elements of different lengths or
+/// `Constants` panics; `try_mul` returns the error instead.
+#[derive(Debug, Clone)]
+pub struct RingPolynomial<T: PolynomialFieldElement> {
+    pub coef: Vec<T>,
+    pub c: Constants<T>,
+}
 
-    fn neg(self) -> Self::Output {
-        Polynomial {
-            coef: self.coef.iter().map(|a| -(*a)).collect(),
+impl<T: PolynomialFieldElement> RingPolynomial<T> {
+    pub fn new(coef: Vec<T>, c: &Constants<T>) -> Self {
+        RingPolynomial::try_new(coef, c).unwrap()
+    }
+
+    pub fn try_new(coef: Vec<T>, c: &Constants<T>) -> Result<Self, NttError> {
+        if !coef.len().is_power_of_two() {
+            return Err(NttError::NonPowerOfTwoLength(coef.len()));
         }
+        c.try_root_of_order(2 * coef.len())?;
+        Ok(RingPolynomial { coef, c: c.clone() })
     }
-}
 
-impl<T: PolynomialFieldElement> Display for Polynomial<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.coef.iter().map(|&x| write!(f, "{} ", x)).collect()
+    /// Reduces `poly` modulo `X^n + 1`.
+    pub fn from_polynomial(poly: Polynomial<T>, n: usize, c: &Constants<T>) -> Self {
+        RingPolynomial::try_from_polynomial(poly, n, c).unwrap()
+    }
+
+    pub fn try_from_polynomial(
+        poly: Polynomial<T>,
+        n: usize,
+        c: &Constants<T>,
+    ) -> Result<Self, NttError> {
+        if !n.is_power_of_two() {
+            return Err(NttError::NonPowerOfTwoLength(n));
+        }
+        let ZERO = T::from(0);
+        let mut coef = vec![ZERO; n];
+        poly.coef.iter().rev().enumerate().for_each(|(i, &x)| {
+            let x = x.rem(c.N);
+            let idx = n - 1 - i % n;
+            coef[idx] = if (i / n).is_multiple_of(2) {
+                (coef[idx] + x).rem(c.N)
+            } else {
+                (coef[idx] + (c.N - x)).rem(c.N)
+            };
+        });
+        RingPolynomial::try_new(coef, c)
+    }
+
+    pub fn len(&self) -> usize {
+        self.coef.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coef.is_empty()
+    }
+
+    /// Product in the ring, failing instead of panicking when the lengths or
+    /// the constants of the operands differ.
+    pub fn try_mul(self, rhs: RingPolynomial<T>) -> Result<Self, NttError> {
+        if self.c.N != rhs.c.N || self.c.w != rhs.c.w {
+            return Err(NttError::ConstantsMismatch);
+        }
+        if self.len() != rhs.len() {
+            return Err(NttError::LengthMismatch {
+                expected: self.len(),
+                found: rhs.len(),
+            });
+        }
+        let a = try_negacyclic_forward(self.coef.into_iter().rev().collect(), &self.c)?;
+        let b = try_negacyclic_forward(rhs.coef.into_iter().rev().collect(), &self.c)?;
+        let mut coef = try_negacyclic_inverse(pointwise(&a, &b, self.c.N), &self.c)?;
+        coef.reverse();
+        Ok(RingPolynomial { coef, c: self.c })
     }
 }
 
-impl<T: PolynomialFieldElement> Index<usize> for Polynomial<T> {
-    type Output = T;
+impl<T: PolynomialFieldElement> Mul<RingPolynomial<T>> for RingPolynomial<T> {
+    type Output = RingPolynomial<T>;
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.coef[index]
+    fn mul(self, rhs: RingPolynomial<T>) -> Self::Output {
+        self.try_mul(rhs).unwrap()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use itertools::Itertools;
-    use rand::Rng;
+/// Element of `Z_N[X]` paired with its `Constants`, so that the arithmetic
+/// operators, including polynomial products, need no extra arguments.
+/// Coefficients are lifted into `Z_N` and stored highest degree first like
+/// `Polynomial`. Products pick an algorithm like `mul_auto`; once they reach
+/// the NTT, `c.w` must have a power-of-two order of at least the product
+/// length. Both operands of a sum or product must carry the same constants.
+#[derive(Debug, Clone)]
+pub struct ModPolynomial<T: PolynomialFieldElement> {
+    pub coef: Vec<T>,
+    pub c: Constants<T>,
+}
 
-    use super::Polynomial;
-    use crate::{
-        ntt::{working_modulus, Constants},
-        numbers::BigInt,
-        polynomial::{diff, fast_mul, PolynomialTrait},
-    };
+impl<T: PolynomialFieldElement> ModPolynomial<T> {
+    pub fn new(coef: Vec<T>, c: &Constants<T>) -> Self {
+        // reduce after trimming, so that the zero polynomial is lifted too
+        ModPolynomial {
+            coef: trim(coef).iter().map(|&x| c.reduce(x)).collect(),
+            c: c.clone(),
+        }
+    }
 
-    #[test]
-    fn test_add() {
-        let a = Polynomial::new(vec![1, 2, 3, 4].iter().map(|&x| BigInt::from(x)).collect());
-        let b = Polynomial::new(vec![1, 2].iter().map(|&x| BigInt::from(x)).collect());
-        println!("{}", a + b);
+    pub fn from_polynomial(poly: Polynomial<T>, c: &Constants<T>) -> Self {
+        ModPolynomial::new(poly.coef, c)
     }
 
-    #[test]
-    fn test_mul() {
-        let ONE = BigInt::from(1);
-        (0..10).for_each(|_| {
-            let n: usize = 1 << rand::thread_rng().gen::<usize>() % (1 << 3);
-            let v1: Vec<BigInt> = (0..n)
-                .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
-                .collect();
-            let v2: Vec<BigInt> = (0..n)
-                .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
-                .collect();
-            let a = Polynomial::new(vec![ONE].into_iter().chain(v1.into_iter()).collect_vec());
-            let b = Polynomial::new(vec![ONE].into_iter().chain(v2.into_iter()).collect_vec());
+    pub fn to_polynomial(&self) -> Polynomial<T> {
+        Polynomial::new(self.coef.clone())
+    }
 
-            let N = BigInt::from((a.len() + b.len()).next_power_of_two());
-            let M = (*a
-                .coef
-                .iter()
-                .max()
-                .unwrap()
-                .max(b.coef.iter().max().unwrap())
-                << 1)
-                * N
-                + 1;
-            let c = working_modulus(N, M);
+    pub fn len(&self) -> usize {
+        self.coef.len()
+    }
 
-            let mul = fast_mul(a, b, &c);
-            assert_eq!(mul[0], ONE);
-        });
+    pub fn is_empty(&self) -> bool {
+        self.coef.is_empty()
     }
 
-    #[test]
-    fn test_diff() {
-        let a = Polynomial::new(vec![3, 2, 1].iter().map(|&x| BigInt::from(x)).collect());
-        let da = diff(a);
-        println!("{}", da);
+    // operands built with different constants live in different rings
+    fn check_constants(&self, rhs: &ModPolynomial<T>) -> Result<(), NttError> {
+        if self.c.N != rhs.c.N || self.c.w != rhs.c.w {
+            return Err(NttError::ConstantsMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn try_add(&self, rhs: &ModPolynomial<T>) -> Result<Self, NttError> {
+        self.check_constants(rhs)?;
+        let sum = self.to_polynomial() + rhs.to_polynomial();
+        Ok(ModPolynomial::new(sum.coef, &self.c))
+    }
+
+    pub fn try_sub(&self, rhs: &ModPolynomial<T>) -> Result<Self, NttError> {
+        self.try_add(&-rhs)
+    }
+
+    pub fn try_mul(&self, rhs: &ModPolynomial<T>) -> Result<Self, NttError> {
+        self.check_constants(rhs)?;
+        let coef = try_convolve(&self.coef, &rhs.coef, &self.c)?;
+        Ok(ModPolynomial::new(coef, &self.c))
+    }
+
+    fn map(&self, f: impl Fn(T) -> T) -> Self {
+        ModPolynomial::new(self.coef.iter().map(|&x| f(x)).collect(), &self.c)
+    }
+}
+
+impl<T: PolynomialFieldElement> Add<&ModPolynomial<T>> for &ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn add(self, rhs: &ModPolynomial<T>) -> Self::Output {
+        self.try_add(rhs).unwrap()
+    }
+}
+
+impl<T: PolynomialFieldElement> Sub<&ModPolynomial<T>> for &ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn sub(self, rhs: &ModPolynomial<T>) -> Self::Output {
+        self.try_sub(rhs).unwrap()
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<&ModPolynomial<T>> for &ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn mul(self, rhs: &ModPolynomial<T>) -> Self::Output {
+        self.try_mul(rhs).unwrap()
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<T> for &ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        let k = self.c.reduce(rhs);
+        self.map(|x| x * k)
+    }
+}
+
+impl<T: PolynomialFieldElement> Neg for &ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn neg(self) -> Self::Output {
+        self.map(|x| -x)
+    }
+}
+
+impl<T: PolynomialFieldElement> Add<ModPolynomial<T>> for ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn add(self, rhs: ModPolynomial<T>) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl<T: PolynomialFieldElement> Sub<ModPolynomial<T>> for ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn sub(self, rhs: ModPolynomial<T>) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<ModPolynomial<T>> for ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn mul(self, rhs: ModPolynomial<T>) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<T> for ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl<T: PolynomialFieldElement> Neg for ModPolynomial<T> {
+    type Output = ModPolynomial<T>;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl<T: PolynomialFieldElement> AddAssign<&ModPolynomial<T>> for ModPolynomial<T> {
+    fn add_assign(&mut self, rhs: &ModPolynomial<T>) {
+        *self = &*self + rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> SubAssign<&ModPolynomial<T>> for ModPolynomial<T> {
+    fn sub_assign(&mut self, rhs: &ModPolynomial<T>) {
+        *self = &*self - rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<&ModPolynomial<T>> for ModPolynomial<T> {
+    fn mul_assign(&mut self, rhs: &ModPolynomial<T>) {
+        *self = &*self * rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> AddAssign<ModPolynomial<T>> for ModPolynomial<T> {
+    fn add_assign(&mut self, rhs: ModPolynomial<T>) {
+        *self += &rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> SubAssign<ModPolynomial<T>> for ModPolynomial<T> {
+    fn sub_assign(&mut self, rhs: ModPolynomial<T>) {
+        *self -= &rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<ModPolynomial<T>> for ModPolynomial<T> {
+    fn mul_assign(&mut self, rhs: ModPolynomial<T>) {
+        *self *= &rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<T> for ModPolynomial<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        *self = &*self * rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> Display for ModPolynomial<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_polynomial().fmt(f)
+    }
+}
+
+pub fn diff<T: PolynomialFieldElement, P: PolynomialTrait<T>>(poly: P) -> P {
+    try_diff(poly).unwrap()
+}
+
+/// Derivative of `poly`; the derivative of a constant is the zero
+/// polynomial `[0]`.
+pub fn try_diff<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
+    mut poly: P,
+) -> Result<P, NttError> {
+    let N = poly.len();
+    if N == 0 {
+        return Err(NttError::ZeroPolynomial);
+    }
+    let _poly = poly.to_vec();
+    for n in (1..N).rev() {
+        poly.set_coef(_poly[n - 1] * T::from(N - n), n);
+    }
+    let ZERO = T::from(0);
+    poly.set_coef(ZERO, 0);
+    poly.set_vec(trim(poly.to_vec()));
+    Ok(poly)
+}
+
+// inverses of lifted `xs` with a single inversion (Montgomery's trick);
+// zeros are skipped and map to zero
+pub(crate) fn batch_invert<T: PolynomialFieldElement>(xs: &[T], c: &Constants<T>) -> Vec<T> {
+    let ONE = c.reduce(T::from(1));
+    let mut prefix = Vec::with_capacity(xs.len());
+    let mut acc = ONE;
+    xs.iter().for_each(|&x| {
+        prefix.push(acc);
+        if !x.is_zero() {
+            acc *= x;
+        }
+    });
+
+    let mut inv = acc.invert();
+    let mut res = vec![c.reduce(T::from(0)); xs.len()];
+    xs.iter().enumerate().rev().for_each(|(i, &x)| {
+        if !x.is_zero() {
+            res[i] = inv * prefix[i];
+            inv *= x;
+        }
+    });
+    res
+}
+
+// antiderivative of ascending `a` with zero constant term, one term longer
+pub(crate) fn antiderivative<T: PolynomialFieldElement>(
+    a: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let ZERO = c.reduce(T::from(0));
+    let degrees: Vec<T> = (1..=a.len()).map(|i| c.reduce(T::from(i))).collect();
+    let inv = batch_invert(&degrees, c);
+    let mut res = vec![ZERO];
+    for (i, &x) in a.iter().enumerate() {
+        let x = c.reduce(x);
+        if degrees[i].is_zero() && !x.is_zero() {
+            return Err(NttError::CharacteristicTooSmall(i + 1));
+        }
+        res.push(x * inv[i]);
+    }
+    Ok(res)
+}
+
+/// Antiderivative of `poly` over `Z_N` with zero constant term, dividing
+/// each coefficient by its new degree with a single modular inversion. A
+/// term whose new degree is a multiple of `N` can only be integrated if it
+/// is zero; otherwise `try_integrate` fails with `CharacteristicTooSmall`.
+pub fn integrate<T: PolynomialFieldElement, P: PolynomialTrait<T>>(poly: P, c: &Constants<T>) -> P {
+    try_integrate(poly, c).unwrap()
+}
+
+pub fn try_integrate<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
+    mut poly: P,
+    c: &Constants<T>,
+) -> Result<P, NttError> {
+    let a: Vec<T> = poly.to_vec().into_iter().rev().collect();
+    let res = antiderivative(&a, c)?;
+    poly.set_vec(trim(res.into_iter().rev().collect()));
+    Ok(poly)
+}
+
+// below this many quotient or divisor terms `div_rem` uses long division
+const NEWTON_THRESHOLD: usize = 32;
+
+// The multiplication thresholds below come from release-build timings of
+// `MulThresholds::mul` over `Fp998244353`, best of seven batches, in
+// microseconds per product of two `n`-term operands (Karatsuba recursing
+// down to a 32-term schoolbook base case):
+//
+//       n  schoolbook  Karatsuba    NTT
+//      32         2.4        2.4    4.4
+//      48         5.2        4.3    8.6
+//      64        10.2        8.5   10.0
+//      96        48.5       15.2   18.3
+//     128       106.7       54.3   17.2
+//     256       504.3      205.2   37.4
+
+// the shorter operand length up to which `convolve` multiplies directly
+const KARATSUBA_THRESHOLD: usize = 32;
+
+// the shorter operand length up to which `convolve` uses Karatsuba
+const NTT_THRESHOLD: usize = 96;
+
+// below this many terms the half-GCD falls back to Euclidean steps
+const HALF_GCD_THRESHOLD: usize = 64;
+
+// how many times longer than the other an operand must be before NTT
+// products go block by block
+const UNBALANCED_RATIO: usize = 4;
+
+// smallest transform an overlap-add block uses
+const MIN_BLOCK_TRANSFORM: usize = 64;
+
+// strips leading zeros, keeping a single zero for the zero polynomial
+pub(crate) fn trim<T: PolynomialFieldElement>(coef: Vec<T>) -> Vec<T> {
+    let ZERO = T::from(0);
+    match coef.iter().position(|&x| x != ZERO) {
+        Some(start) => coef[start..].to_vec(),
+        None => vec![ZERO],
+    }
+}
+
+// exact linear convolution `a * b` of length `a.len() + b.len() - 1`
+pub(crate) fn convolve<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Vec<T> {
+    try_convolve(a, b, c).unwrap()
+}
+
+pub(crate) fn try_convolve<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    MulThresholds::default().try_convolve(a, b, c)
+}
+
+/// Operand lengths at which `mul_auto` changes algorithm: schoolbook while
+/// the shorter operand has at most `karatsuba` terms, Karatsuba up to `ntt`
+/// terms and NTT beyond. Both compare against the shorter operand, since
+/// Karatsuba cuts a much longer one into blocks and so stays cheap for
+/// unbalanced products; past `ntt` such products go through
+/// `mul_unbalanced`. The defaults are the crossovers of the timings noted
+/// in the source next to them; the "Multiplication Crossover" benches
+/// re-measure them on other machines and element types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MulThresholds {
+    pub karatsuba: usize,
+    pub ntt: usize,
+}
+
+impl Default for MulThresholds {
+    fn default() -> Self {
+        MulThresholds {
+            karatsuba: KARATSUBA_THRESHOLD,
+            ntt: NTT_THRESHOLD,
+        }
+    }
+}
+
+impl MulThresholds {
+    /// `lhs * rhs` mod `c.N` by the algorithm these thresholds select.
+    pub fn mul<T: PolynomialFieldElement>(
+        &self,
+        lhs: &Polynomial<T>,
+        rhs: &Polynomial<T>,
+        c: &Constants<T>,
+    ) -> Polynomial<T> {
+        self.try_mul(lhs, rhs, c).unwrap()
+    }
+
+    pub fn try_mul<T: PolynomialFieldElement>(
+        &self,
+        lhs: &Polynomial<T>,
+        rhs: &Polynomial<T>,
+        c: &Constants<T>,
+    ) -> Result<Polynomial<T>, NttError> {
+        if lhs.coef.is_empty() || rhs.coef.is_empty() {
+            return Ok(Polynomial::new(vec![c.reduce(T::from(0))]));
+        }
+        let coef = trim(self.try_convolve(&lhs.coef, &rhs.coef, c)?);
+        Ok(Polynomial {
+            coef: coef.into_iter().map(|x| c.reduce(x)).collect(),
+        })
+    }
+
+    // lifted linear convolution `a * b` of length `a.len() + b.len() - 1`
+    fn try_convolve<T: PolynomialFieldElement>(
+        &self,
+        a: &[T],
+        b: &[T],
+        c: &Constants<T>,
+    ) -> Result<Vec<T>, NttError> {
+        if a.is_empty() || b.is_empty() {
+            return Ok(vec![]);
+        }
+        let a: Vec<T> = a.iter().map(|&x| c.reduce(x)).collect();
+        let b: Vec<T> = b.iter().map(|&x| c.reduce(x)).collect();
+        let short = a.len().min(b.len());
+        if short <= self.karatsuba {
+            return Ok(schoolbook(&a, &b, c.reduce(T::from(0))));
+        }
+        if short <= self.ntt {
+            return Ok(karatsuba(&a, &b, c.reduce(T::from(0)), self.karatsuba));
+        }
+
+        if is_unbalanced(a.len(), b.len()) {
+            return try_overlap_add(&a, &b, c);
+        }
+
+        let len = a.len() + b.len() - 1;
+        let plan = NttPlan::try_new(c, len.next_power_of_two())?;
+        let ZERO = T::from(0);
+        let mut fa = a;
+        let mut fb = b;
+        fa.resize(plan.n, ZERO);
+        fb.resize(plan.n, ZERO);
+        plan.forward_in_place(&mut fa);
+        plan.forward_in_place(&mut fb);
+        let mut res = plan.inverse(pointwise(&fa, &fb, c.N));
+        res.truncate(len);
+        Ok(res)
+    }
+}
+
+/// `lhs * rhs` mod `c.N`, choosing schoolbook, Karatsuba or NTT
+/// multiplication by the operand lengths with the default `MulThresholds`.
+pub fn mul_auto<T: PolynomialFieldElement>(
+    lhs: &Polynomial<T>,
+    rhs: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_mul_auto(lhs, rhs, c).unwrap()
+}
+
+pub fn try_mul_auto<T: PolynomialFieldElement>(
+    lhs: &Polynomial<T>,
+    rhs: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    MulThresholds::default().try_mul(lhs, rhs, c)
+}
+
+// first `k` terms of `1 / b` for ascending, lifted `b` with `b[0] != 0`,
+// doubling the precision with each Newton step `g = g * (2 - b * g)`
+pub(crate) fn inv_series<T: PolynomialFieldElement>(
+    b: &[T],
+    k: usize,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let TWO = c.reduce(T::from(2));
+    let mut g = vec![c.reduce(b[0]).invert()];
+    while g.len() < k {
+        let t = (2 * g.len()).min(k);
+        let mut e = try_convolve(&b[..b.len().min(t)], &g, c)?;
+        e.truncate(t);
+        e.iter_mut().for_each(|x| *x = -*x);
+        e[0] += TWO;
+        g = try_convolve(&g, &e, c)?;
+        g.truncate(t);
+    }
+    Ok(g)
+}
+
+// schoolbook division in the coefficients' own field
+fn long_division<T: PolynomialFieldElement>(a: &[T], b: &[T]) -> (Vec<T>, Vec<T>) {
+    let b = trim(b.to_vec());
+    assert!(!b[0].is_zero(), "division by the zero polynomial");
+    let mut r = trim(a.to_vec());
+    if r.len() < b.len() {
+        return (vec![T::from(0)], r);
+    }
+    let lead_inv = b[0].invert();
+    let k = r.len() - b.len() + 1;
+    let mut q = Vec::with_capacity(k);
+    for i in 0..k {
+        let coef = r[i] * lead_inv;
+        b.iter()
+            .enumerate()
+            .for_each(|(j, &x)| r[i + j] = r[i + j] - coef * x);
+        q.push(coef);
+    }
+    (q, trim(r[k..].to_vec()))
+}
+
+/// Quotient and remainder of `a / b` over `Z_N`, computing the reversed
+/// quotient as a power series with Newton iteration. `c.w` must have a
+/// power-of-two order of at least `2 * a.len()`.
+pub fn div_rem<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    b: Polynomial<T>,
+    c: &Constants<T>,
+) -> (Polynomial<T>, Polynomial<T>) {
+    try_div_rem(a, b, c).unwrap()
+}
+
+pub fn try_div_rem<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    b: Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<(Polynomial<T>, Polynomial<T>), NttError> {
+    let a = trim(a.coef.iter().map(|&x| c.reduce(x)).collect());
+    let b = trim(b.coef.iter().map(|&x| c.reduce(x)).collect());
+    if b[0].is_zero() {
+        return Err(NttError::ZeroPolynomial);
+    }
+    if a.len() < b.len() {
+        return Ok((
+            Polynomial::new(vec![c.reduce(T::from(0))]),
+            Polynomial::new(a),
+        ));
+    }
+
+    let k = a.len() - b.len() + 1;
+    if k.min(b.len()) <= NEWTON_THRESHOLD {
+        let (q, r) = long_division(&a, &b);
+        return Ok((Polynomial::new(q), Polynomial::new(r)));
+    }
+
+    // desc coefficients read in ascending order are the reversed polynomials
+    let mut q = try_convolve(&a[..k], &inv_series(&b, k, c)?, c)?;
+    q.truncate(k);
+    let qb = try_convolve(&q, &b, c)?;
+    let r = a[k..].iter().zip(&qb[k..]).map(|(&x, &y)| x - y).collect();
+    Ok((Polynomial::new(q), Polynomial::new(trim(r))))
+}
+
+// 2x2 polynomial matrix `[[m[0], m[1]], [m[2], m[3]]]` of stripped,
+// descending coefficient vectors, tracking Euclidean remainder sequences
+type Matrix<T> = [Vec<T>; 4];
+
+// strips every leading zero, so the zero polynomial is empty
+pub(crate) fn strip<T: PolynomialFieldElement>(mut v: Vec<T>) -> Vec<T> {
+    let start = v.iter().position(|x| !x.is_zero()).unwrap_or(v.len());
+    v.drain(..start);
+    v
+}
+
+// `a + s * b` for stripped descending vectors, `s` one or minus one
+pub(crate) fn add_scaled<T: PolynomialFieldElement>(a: &[T], b: &[T], negate: bool) -> Vec<T> {
+    let b = b.iter().map(|&x| if negate { -x } else { x });
+    let res = a
+        .iter()
+        .rev()
+        .zip_longest(b.rev())
+        .map(|p| match p {
+            Both(&x, y) => x + y,
+            Left(&x) => x,
+            Right(y) => y,
+        })
+        .collect::<Vec<T>>();
+    strip(res.into_iter().rev().collect())
+}
+
+pub(crate) fn mul_stripped<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    Ok(strip(try_convolve(a, b, c)?))
+}
+
+fn identity<T: PolynomialFieldElement>(c: &Constants<T>) -> Matrix<T> {
+    let ONE = c.reduce(T::from(1));
+    [vec![ONE], vec![], vec![], vec![ONE]]
+}
+
+// `x * y`
+fn mat_mul<T: PolynomialFieldElement>(
+    x: &Matrix<T>,
+    y: &Matrix<T>,
+    c: &Constants<T>,
+) -> Result<Matrix<T>, NttError> {
+    let entry = |i: usize, j: usize| -> Result<Vec<T>, NttError> {
+        Ok(add_scaled(
+            &mul_stripped(&x[2 * i], &y[j], c)?,
+            &mul_stripped(&x[2 * i + 1], &y[2 + j], c)?,
+            false,
+        ))
+    };
+    Ok([entry(0, 0)?, entry(0, 1)?, entry(1, 0)?, entry(1, 1)?])
+}
+
+// `m * (a, b)`
+fn apply<T: PolynomialFieldElement>(
+    m: &Matrix<T>,
+    (a, b): &(Vec<T>, Vec<T>),
+    c: &Constants<T>,
+) -> Result<(Vec<T>, Vec<T>), NttError> {
+    Ok((
+        add_scaled(
+            &mul_stripped(&m[0], a, c)?,
+            &mul_stripped(&m[1], b, c)?,
+            false,
+        ),
+        add_scaled(
+            &mul_stripped(&m[2], a, c)?,
+            &mul_stripped(&m[3], b, c)?,
+            false,
+        ),
+    ))
+}
+
+// one Euclidean step `(a, b) -> (b, a mod b)` for nonzero `b`, recorded in
+// `m` as `[[0, 1], [1, -q]] * m`
+fn euclid_step<T: PolynomialFieldElement>(
+    m: &mut Matrix<T>,
+    p: &mut (Vec<T>, Vec<T>),
+    c: &Constants<T>,
+) -> Result<(), NttError> {
+    let (q, r) = try_div_rem(
+        Polynomial::new(p.0.clone()),
+        Polynomial::new(p.1.clone()),
+        c,
+    )?;
+    let q = strip(q.coef);
+    *p = (std::mem::take(&mut p.1), strip(r.coef));
+    let m2 = add_scaled(&m[0], &mul_stripped(&q, &m[2], c)?, true);
+    let m3 = add_scaled(&m[1], &mul_stripped(&q, &m[3], c)?, true);
+    *m = [std::mem::take(&mut m[2]), std::mem::take(&mut m[3]), m2, m3];
+    Ok(())
+}
+
+// half-GCD for `a.len() > b.len()`: a product `m` of Euclidean steps such
+// that `m * (a, b)` is the first pair of consecutive remainders whose
+// second has at most `ceil(a.len() / 2)` terms, found from the leading
+// halves of `a` and `b` alone
+fn half_gcd<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Matrix<T>, NttError> {
+    let k = a.len().div_ceil(2);
+    let mut m = identity(c);
+    if b.len() <= k {
+        return Ok(m);
+    }
+    if a.len() <= HALF_GCD_THRESHOLD {
+        let mut p = (a.to_vec(), b.to_vec());
+        while p.1.len() > k {
+            euclid_step(&mut m, &mut p, c)?;
+        }
+        return Ok(m);
+    }
+
+    // the top halves fix the quotients down to `k` terms
+    let mut m = half_gcd(&a[..a.len() - k], &b[..b.len() - k], c)?;
+    let mut p = apply(&m, &(a.to_vec(), b.to_vec()), c)?;
+    if p.1.len() <= k {
+        return Ok(m);
+    }
+    euclid_step(&mut m, &mut p, c)?;
+    if p.1.len() <= k {
+        return Ok(m);
+    }
+    let j = 2 * k - (p.0.len() - 1);
+    let h = half_gcd(&p.0[..p.0.len() - j], &p.1[..p.1.len() - j], c)?;
+    mat_mul(&h, &m, c)
+}
+
+// last nonzero remainder `g` of `(a, b)` with the matrix taking `(a, b)`
+// to `(g, 0)`, so that `g = m[0] * a + m[1] * b`
+pub(crate) fn remainder_gcd<T: PolynomialFieldElement>(
+    a: Vec<T>,
+    b: Vec<T>,
+    c: &Constants<T>,
+) -> Result<(Vec<T>, Matrix<T>), NttError> {
+    let mut m = identity(c);
+    let mut p = (a, b);
+    while !p.1.is_empty() {
+        if p.0.len() > p.1.len() && p.1.len() > HALF_GCD_THRESHOLD {
+            let h = half_gcd(&p.0, &p.1, c)?;
+            p = apply(&h, &p, c)?;
+            m = mat_mul(&h, &m, c)?;
+            if p.1.is_empty() {
+                break;
+            }
+        }
+        euclid_step(&mut m, &mut p, c)?;
+    }
+    Ok((p.0, m))
+}
+
+// lifted, stripped coefficients of `p`
+pub(crate) fn lift<T: PolynomialFieldElement>(p: &Polynomial<T>, c: &Constants<T>) -> Vec<T> {
+    strip(p.coef.iter().map(|&x| c.reduce(x)).collect())
+}
+
+// a stripped vector as a `Polynomial`, keeping a lifted zero term for zero
+pub(crate) fn unstrip<T: PolynomialFieldElement>(v: Vec<T>, c: &Constants<T>) -> Polynomial<T> {
+    match v.is_empty() {
+        true => Polynomial::new(vec![c.reduce(T::from(0))]),
+        false => Polynomial::new(v),
+    }
+}
+
+// remainders modulo a fixed stripped, lifted `m` with at least two terms,
+// reusing one inverse of the reversed `m`, long enough for the quotient of
+// any product of two remainders
+struct Reducer<T: PolynomialFieldElement> {
+    m: Vec<T>,
+    inv: Vec<T>,
+}
+
+impl<T: PolynomialFieldElement> Reducer<T> {
+    fn new(m: Vec<T>, c: &Constants<T>) -> Result<Self, NttError> {
+        let inv = inv_series(&m, m.len() - 1, c)?;
+        Ok(Reducer { m, inv })
+    }
+
+    fn reduce(&self, a: Vec<T>, c: &Constants<T>) -> Result<Vec<T>, NttError> {
+        let a = strip(a);
+        if a.len() < self.m.len() {
+            return Ok(a);
+        }
+        let k = a.len() - self.m.len() + 1;
+        if k > self.inv.len() {
+            let (_, r) = try_div_rem(Polynomial::new(a), Polynomial::new(self.m.clone()), c)?;
+            return Ok(strip(r.coef));
+        }
+        let mut q = try_convolve(&a[..k], &self.inv[..k], c)?;
+        q.truncate(k);
+        let qm = try_convolve(&q, &self.m, c)?;
+        Ok(strip(
+            a[k..].iter().zip(&qm[k..]).map(|(&x, &y)| x - y).collect(),
+        ))
+    }
+}
+
+// `base^exp mod f` for stripped, lifted `base` and nonzero `f`, squaring
+// and multiplying from the top bit of `exp` down
+pub(crate) fn pow_mod_stripped<T: PolynomialFieldElement>(
+    base: &[T],
+    exp: BigInt,
+    f: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    // everything is zero modulo a constant
+    if f.len() == 1 {
+        return Ok(vec![]);
+    }
+    let r = Reducer::new(f.to_vec(), c)?;
+    let base = r.reduce(base.to_vec(), c)?;
+    let mut res = vec![c.reduce(T::from(1))];
+    (0..exp.bits() as usize).rev().try_for_each(|i| {
+        res = r.reduce(mul_stripped(&res, &res, c)?, c)?;
+        if !(exp >> i).is_even() {
+            res = r.reduce(mul_stripped(&res, &base, c)?, c)?;
+        }
+        Ok(())
+    })?;
+    Ok(res)
+}
+
+/// `base^exp mod modulus` over `Z_N` by repeated squaring, taking every
+/// remainder with one precomputed inverse of the reversed modulus, so that
+/// each step costs a few `mul_auto` products. `c.w` must have a
+/// power-of-two order of at least twice the modulus length.
+pub fn pow_mod<T: PolynomialFieldElement>(
+    base: &Polynomial<T>,
+    exp: BigInt,
+    modulus: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_pow_mod(base, exp, modulus, c).unwrap()
+}
+
+pub fn try_pow_mod<T: PolynomialFieldElement>(
+    base: &Polynomial<T>,
+    exp: BigInt,
+    modulus: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let m = lift(modulus, c);
+    if m.is_empty() {
+        return Err(NttError::ZeroPolynomial);
+    }
+    Ok(unstrip(pow_mod_stripped(&lift(base, c), exp, &m, c)?, c))
+}
+
+// one row of `a * b`, for `b` row-major with `cols` columns
+fn coef_row<T: PolynomialFieldElement>(row: &[T], b: &[T], cols: usize, zero: T) -> Vec<T> {
+    let mut out = vec![zero; cols];
+    row.iter().zip(b.chunks(cols)).for_each(|(&x, r)| {
+        out.iter_mut().zip(r).for_each(|(o, &y)| *o += x * y);
+    });
+    out
+}
+
+// `a * b` for row-major `a` with `inner` columns and `b` with `cols`
+#[cfg(feature = "parallel")]
+fn coef_mat_mul<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    inner: usize,
+    cols: usize,
+    zero: T,
+) -> Vec<T> {
+    a.par_chunks(inner)
+        .flat_map_iter(|row| coef_row(row, b, cols, zero))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn coef_mat_mul<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    inner: usize,
+    cols: usize,
+    zero: T,
+) -> Vec<T> {
+    a.chunks(inner)
+        .flat_map(|row| coef_row(row, b, cols, zero))
+        .collect()
+}
+
+/// `f(g) mod h` over `Z_N` by Brent-Kung modular composition: with
+/// `k = ceil(sqrt(f.len()))`, the blocks of `k` coefficients of `f` form the
+/// rows of a `k x k` matrix, its product with the `k x deg h` matrix of
+/// `g^0, ..., g^(k - 1) mod h` evaluates every block at once, and Horner's
+/// rule in `g^k` joins them, so only about `2k` products modulo `h` are
+/// needed instead of one per coefficient.
+pub fn compose_mod<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    g: &Polynomial<T>,
+    h: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_compose_mod(f, g, h, c).unwrap()
+}
+
+pub fn try_compose_mod<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    g: &Polynomial<T>,
+    h: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let h = lift(h, c);
+    if h.is_empty() {
+        return Err(NttError::ZeroPolynomial);
+    }
+    let f: Vec<T> = lift(f, c).into_iter().rev().collect();
+    if f.is_empty() || h.len() == 1 {
+        return Ok(unstrip(vec![], c));
+    }
+    let ZERO = c.reduce(T::from(0));
+    let n = h.len() - 1;
+    let r = Reducer::new(h, c)?;
+    let g = r.reduce(lift(g, c), c)?;
+
+    // baby steps g^0, ..., g^k
+    let k = (1..).find(|&k| k * k >= f.len()).unwrap();
+    let mut pows = vec![vec![c.reduce(T::from(1))]];
+    (0..k).try_for_each(|_| {
+        let next = r.reduce(mul_stripped(pows.last().unwrap(), &g, c)?, c)?;
+        pows.push(next);
+        Ok::<(), NttError>(())
+    })?;
+
+    // row j holds f[j k], ..., f[j k + k - 1]; row i of `powers` holds g^i
+    // right-aligned in `n` terms, so row j of the product is block j
+    let mut coefs = f.clone();
+    coefs.resize(k * k, ZERO);
+    let mut powers = vec![ZERO; k * n];
+    pows[..k]
+        .iter()
+        .enumerate()
+        .for_each(|(i, p)| powers[(i + 1) * n - p.len()..(i + 1) * n].copy_from_slice(p));
+    let blocks = coef_mat_mul(&coefs, &powers, k, n, ZERO);
+
+    let mut res = vec![];
+    for block in blocks.chunks(n).rev() {
+        res = r.reduce(mul_stripped(&res, &pows[k], c)?, c)?;
+        res = add_scaled(&res, &strip(block.to_vec()), false);
+    }
+    Ok(unstrip(res, c))
+}
+
+/// Monic greatest common divisor of `a` and `b` over the prime field
+/// `Z_N`, zero only if both are. Long remainder sequences go through the
+/// half-GCD, whose products use `mul_auto`; `c.w` must have a power-of-two
+/// order of at least twice the longer length.
+pub fn gcd<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    b: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_gcd(a, b, c).unwrap()
+}
+
+pub fn try_gcd<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    b: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    Ok(try_xgcd(a, b, c)?.0)
+}
+
+// `(g, s, t)` as returned by `xgcd`
+type Bezout<T> = (Polynomial<T>, Polynomial<T>, Polynomial<T>);
+
+/// `(g, s, t)` with `g = gcd(a, b)` monic and `s * a + t * b = g`.
+pub fn xgcd<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    b: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Bezout<T> {
+    try_xgcd(a, b, c).unwrap()
+}
+
+pub fn try_xgcd<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    b: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Bezout<T>, NttError> {
+    let (g, m) = remainder_gcd(lift(a, c), lift(b, c), c)?;
+    if g.is_empty() {
+        return Ok((unstrip(g, c), unstrip(vec![], c), unstrip(vec![], c)));
+    }
+    let [s, t, _, _] = m;
+    let lead_inv = g[0].invert();
+    let scale = |v: Vec<T>| unstrip(v.into_iter().map(|x| x * lead_inv).collect(), c);
+    Ok((scale(g), scale(s), scale(t)))
+}
+
+/// Inverse of `a` modulo `m`, the `s` with `s * a = 1 mod m` and fewer
+/// terms than `m`. `try_inverse_mod` fails with `NotInvertible` unless
+/// `gcd(a, m) = 1`.
+pub fn inverse_mod<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    m: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_inverse_mod(a, m, c).unwrap()
+}
+
+pub fn try_inverse_mod<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    m: &Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let (_, a) = try_div_rem(a.clone(), m.clone(), c)?;
+    let (g, s, _) = try_xgcd(&a, m, c)?;
+    if g.coef.len() != 1 || g.coef[0].is_zero() {
+        return Err(NttError::NotInvertible);
+    }
+    // every polynomial is zero modulo a constant
+    if lift(m, c).len() == 1 {
+        return Ok(unstrip(vec![], c));
+    }
+    Ok(s)
+}
+
+// `div_rem` over the coefficient type's own field where it has one, long
+// division otherwise
+fn operator_div_rem<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    b: Polynomial<T>,
+) -> Result<(Polynomial<T>, Polynomial<T>), NttError> {
+    if let Some(c) = T::field_constants() {
+        return try_div_rem(a, b, &c);
+    }
+    b.try_degree()?;
+    let (q, r) = long_division(&a.coef, &b.coef);
+    Ok((Polynomial::new(q), Polynomial::new(r)))
+}
+
+/// Panics when `rhs` is the zero polynomial; see `Polynomial::try_div`.
+impl<T: PolynomialFieldElement> Div<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn div(self, rhs: Polynomial<T>) -> Self::Output {
+        self.try_div(rhs).unwrap()
+    }
+}
+
+/// Panics when `rhs` is the zero polynomial; see `Polynomial::try_rem`.
+impl<T: PolynomialFieldElement> Rem<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn rem(self, rhs: Polynomial<T>) -> Self::Output {
+        self.try_rem(rhs).unwrap()
+    }
+}
+
+impl<T: PolynomialFieldElement> Add<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn add(self, rhs: Polynomial<T>) -> Self::Output {
+        Polynomial {
+            coef: self
+                .coef
+                .iter()
+                .rev()
+                .zip_longest(rhs.coef.iter().rev())
+                .map(|p| match p {
+                    Both(&a, &b) => a + b,
+                    Left(&a) => a,
+                    Right(&b) => b,
+                })
+                .rev()
+                .collect(),
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement> Sub<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn sub(self, rhs: Polynomial<T>) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl<T: PolynomialFieldElement> Neg for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn neg(self) -> Self::Output {
+        Polynomial {
+            coef: self.coef.iter().map(|a| -(*a)).collect(),
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement> Add<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn add(self, rhs: &Polynomial<T>) -> Self::Output {
+        self.clone() + rhs.clone()
+    }
+}
+
+impl<T: PolynomialFieldElement> Sub<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn sub(self, rhs: &Polynomial<T>) -> Self::Output {
+        self.clone() - rhs.clone()
+    }
+}
+
+impl<T: PolynomialFieldElement> Neg for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+/// Scales every coefficient, in the coefficients' own field.
+impl<T: PolynomialFieldElement> Mul<T> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Polynomial {
+            coef: self.coef.iter().map(|&a| a * rhs).collect(),
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<T> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.clone() * rhs
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<T> for Polynomial<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        self.coef.iter_mut().for_each(|a| *a = *a * rhs);
+    }
+}
+
+impl<T: PolynomialFieldElement> AddAssign<Polynomial<T>> for Polynomial<T> {
+    fn add_assign(&mut self, rhs: Polynomial<T>) {
+        *self = &*self + &rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> SubAssign<Polynomial<T>> for Polynomial<T> {
+    fn sub_assign(&mut self, rhs: Polynomial<T>) {
+        *self = &*self - &rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement> Display for Polynomial<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.coef.iter().map(|&x| write!(f, "{} ", x)).collect()
+    }
+}
+
+impl<T: PolynomialFieldElement> Index<usize> for Polynomial<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.coef[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::Invert;
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use super::{
+        compose_mod, gcd, inverse_mod, pow_mod, trim, try_inverse_mod, try_pow_mod, xgcd,
+        Polynomial,
+    };
+    use crate::{
+        error::NttError,
+        ntt::{working_modulus, Constants, NttPlan},
+        numbers::{BigInt, Fp998244353, Mod64, NttFieldElement},
+        polynomial::{
+            batch_invert, cached_modulus, diff, div_rem, fast_mul, fast_mul_with_plan, integrate,
+            mul_auto, mul_brute, mul_karatsuba, mul_unbalanced, multiply, try_diff, try_div_rem,
+            try_fast_mul_with_plan, try_integrate, try_multiply, CoefficientOrder, ModPolynomial,
+            MulThresholds, PolynomialTrait, RingPolynomial,
+        },
+    };
+
+    #[test]
+    fn test_add() {
+        let a = Polynomial::new(vec![1, 2, 3, 4].iter().map(|&x| BigInt::from(x)).collect());
+        let b = Polynomial::new(vec![1, 2].iter().map(|&x| BigInt::from(x)).collect());
+        println!("{}", a + b);
+    }
+
+    #[test]
+    fn test_mul() {
+        let ONE = BigInt::from(1);
+        (0..10).for_each(|_| {
+            let n: usize = 1 << rand::thread_rng().gen::<usize>() % (1 << 3);
+            let v1: Vec<BigInt> = (0..n)
+                .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
+                .collect();
+            let v2: Vec<BigInt> = (0..n)
+                .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
+                .collect();
+            let a = Polynomial::new(vec![ONE].into_iter().chain(v1.into_iter()).collect_vec());
+            let b = Polynomial::new(vec![ONE].into_iter().chain(v2.into_iter()).collect_vec());
+
+            let N = BigInt::from((a.len() + b.len()).next_power_of_two());
+            let M = (*a
+                .coef
+                .iter()
+                .max()
+                .unwrap()
+                .max(b.coef.iter().max().unwrap())
+                << 1)
+                * N
+                + 1;
+            let c = working_modulus(N, M);
+
+            let mul = fast_mul(a, b, &c);
+            assert_eq!(mul[0], ONE);
+        });
+    }
+
+    #[test]
+    fn test_mul_with_plan() {
+        let n = 16;
+        let N = BigInt::from(2 * n);
+        let c = working_modulus(N, BigInt::from(1 << 20));
+        let plan = NttPlan::new(&c, 2 * n);
+        (0..4).for_each(|_| {
+            let a = Polynomial::new(
+                (0..n)
+                    .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6) + 1))
+                    .collect_vec(),
+            );
+            let b = Polynomial::new(
+                (0..n)
+                    .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6) + 1))
+                    .collect_vec(),
+            );
+            let expected = mul_brute(a.clone(), b.clone());
+            let mul = fast_mul_with_plan(a, b, &plan);
+            assert_eq!(mul.coef, expected.coef[..2 * n - 1]);
+        });
+    }
+
+    #[test]
+    fn test_mul_mod64() {
+        let n = 64;
+        let c = working_modulus(Mod64::from(2 * n), Mod64::from(1_u64 << 24));
+        let a = Polynomial::new(
+            (0..n)
+                .map(|_| Mod64::from(rand::thread_rng().gen::<u32>() % (1 << 8) + 1))
+                .collect_vec(),
+        );
+        let b = Polynomial::new(
+            (0..n)
+                .map(|_| Mod64::from(rand::thread_rng().gen::<u32>() % (1 << 8) + 1))
+                .collect_vec(),
+        );
+        let expected = mul_brute(a.clone(), b.clone());
+        let mul = fast_mul(a, b, &c);
+        assert_eq!(mul.coef, expected.coef[..2 * n - 1]);
+    }
+
+    #[test]
+    fn test_mul_fixed_modulus() {
+        let n = 64;
+        let c = Constants::<Fp998244353>::for_size(2 * n);
+        let a = Polynomial::new(
+            (0..n)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                .collect_vec(),
+        );
+        let b = Polynomial::new(
+            (0..n)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                .collect_vec(),
+        );
+        let expected = mul_brute(a.clone(), b.clone());
+        let mul = fast_mul(a, b, &c);
+        assert_eq!(mul.coef, expected.coef[..2 * n - 1]);
+    }
+
+    #[test]
+    fn test_multiply() {
+        let a = Polynomial::new(
+            (0..100)
+                .map(|_| Mod64::from(rand::thread_rng().gen::<u32>() % (1 << 16) + 1))
+                .collect_vec(),
+        );
+        let b = Polynomial::new(
+            (0..37)
+                .map(|_| Mod64::from(rand::thread_rng().gen::<u32>() % (1 << 20) + 1))
+                .collect_vec(),
+        );
+        let expected = mul_brute(a.clone(), b.clone());
+        let prod = multiply(&a, &b);
+        assert_eq!(prod.coef, expected.coef[..136]);
+
+        let a = Polynomial::new([3, 2, 1].iter().map(|&x| BigInt::from(x)).collect());
+        let b = Polynomial::new([1, 1].iter().map(|&x| BigInt::from(x)).collect());
+        assert_eq!(
+            multiply(&a, &b).coef,
+            [3, 5, 3, 1].iter().map(|&x| BigInt::from(x)).collect_vec()
+        );
+        let zero = Polynomial::new(vec![BigInt::from(0); 2]);
+        assert_eq!(multiply(&a, &zero).coef, vec![BigInt::from(0)]);
+
+        let big = Polynomial::new(vec![Mod64::from(1_u64 << 40); 4]);
+        assert_eq!(try_multiply(&big, &big).unwrap_err(), NttError::Overflow);
+        let fixed = Polynomial::new(vec![Fp998244353::from(2); 4]);
+        assert_eq!(
+            try_multiply(&fixed, &fixed).unwrap_err(),
+            NttError::InvalidModulus
+        );
+    }
+
+    #[test]
+    fn test_modulus_cache() {
+        let c = cached_modulus(1 << 6, Mod64::from(1_u64 << 20)).unwrap();
+        let d = cached_modulus(1 << 4, Mod64::from(1_u64 << 10)).unwrap();
+        assert_eq!(c.N, d.N);
+        let e = cached_modulus(1 << 8, Mod64::from(1_u64 << 10)).unwrap();
+        assert!(e.N > Mod64::from(1_u64 << 20));
+        assert_eq!(e.order(), 1 << 8);
+    }
+
+    #[test]
+    fn test_ring_mul() {
+        let n = 32;
+        let c = Constants::<Fp998244353>::for_size(2 * n);
+        let a = Polynomial::new(
+            (0..n)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                .collect_vec(),
+        );
+        let b = Polynomial::new(
+            (0..n)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                .collect_vec(),
+        );
+        let full = mul_brute(a.clone(), b.clone());
+        // drop the trailing zero `mul_brute` leaves below the constant term
+        let full = Polynomial::new(full.coef[..2 * n - 1].to_vec());
+        let expected = RingPolynomial::from_polynomial(full, n, &c);
+
+        let prod = RingPolynomial::new(a.coef, &c) * RingPolynomial::new(b.coef, &c);
+        assert_eq!(prod.coef, expected.coef);
+    }
+
+    fn random_fp(n: usize) -> Polynomial<Fp998244353> {
+        Polynomial::new(
+            (0..n)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>() % 998244353 + 1))
+                .collect_vec(),
+        )
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        [(200, 70), (300, 5), (40, 39)].iter().for_each(|&(n, m)| {
+            let a = random_fp(n);
+            let b = random_fp(m);
+            let (q, r) = div_rem(a.clone(), b.clone(), &c);
+            assert_eq!(q.coef, (a.clone() / b.clone()).coef);
+            assert_eq!(r.coef, (a.clone() % b.clone()).coef);
+            assert!(r.len() < m);
+
+            let qb = mul_brute(q, b);
+            let qb = Polynomial::new(qb.coef[..qb.len() - 1].to_vec());
+            assert_eq!((qb + r).coef, a.coef);
+        });
+
+        let a = random_fp(10);
+        let (q, r) = div_rem(a.clone(), random_fp(20), &c);
+        assert!(q[0].is_zero());
+        assert_eq!(r.coef, a.coef);
+    }
+
+    #[test]
+    fn test_div_operators() {
+        // (x^2 - 1) / (x - 1) = x + 1
+        let ONE = BigInt::from(1);
+        let ZERO = BigInt::from(0);
+        let a = Polynomial::new(vec![ONE, ZERO, -ONE]);
+        let b = Polynomial::new(vec![ONE, -ONE]);
+        assert_eq!((a.clone() / b.clone()).coef, vec![ONE, ONE]);
+        assert_eq!((a.clone() % b).coef, vec![ZERO]);
+        let zero = Polynomial::new(vec![ZERO, ZERO]);
+        assert_eq!(
+            a.clone().try_div(zero.clone()).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+        assert_eq!(a.try_rem(zero).unwrap_err(), NttError::ZeroPolynomial);
+        let zero = Polynomial::new(vec![Fp998244353::from(0)]);
+        assert_eq!(
+            random_fp(5).try_rem(zero).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+        assert_eq!(Polynomial::<BigInt>::new(vec![]).max(), ZERO);
+
+        // quotient and divisor both past NEWTON_THRESHOLD
+        let a = random_fp(300);
+        let b = random_fp(120);
+        let q = a.clone() / b.clone();
+        let r = a.clone() % b.clone();
+        assert_eq!(q.len(), 181);
+        assert!(r.len() < 120);
+        let qb = mul_brute(q, b);
+        let qb = Polynomial::new(qb.coef[..qb.len() - 1].to_vec());
+        assert_eq!((qb + r).coef, a.coef);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // 3x^2 + 2x + 1 at x = 2
+        let a = Polynomial::new([3, 2, 1].iter().map(|&x| BigInt::from(x)).collect());
+        assert_eq!(a.evaluate(BigInt::from(2)), BigInt::from(17));
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Polynomial::new(vec![3, 2, 1].iter().map(|&x| BigInt::from(x)).collect());
+        let da = diff(a);
+        println!("{}", da);
+    }
+
+    #[test]
+    fn test_integrate() {
+        let c = Constants::<Fp998244353>::for_size(1 << 4);
+        let a = random_fp(50);
+        let ia = integrate(a.clone(), &c);
+        assert_eq!(ia.len(), a.len() + 1);
+        assert_eq!(diff(ia).coef, a.coef);
+
+        let xs = (0..20).map(Fp998244353::from).collect_vec();
+        batch_invert(&xs, &c).iter().zip(&xs).for_each(|(&y, &x)| {
+            let expected = if x.is_zero() { x } else { x.invert() };
+            assert_eq!(y, expected);
+        });
+
+        // over Z_5, x^4 has no antiderivative but x^5 + 3x^3 does
+        let c = Constants {
+            N: Mod64::from(5_u64),
+            w: Mod64::from(4_u64),
+        };
+        let a = Polynomial::new([1, 0, 0, 0, 0].iter().map(|&x| Mod64::from(x)).collect());
+        assert_eq!(
+            try_integrate(a, &c).unwrap_err(),
+            NttError::CharacteristicTooSmall(5)
+        );
+        let a = Polynomial::new([1, 0, 3, 0, 0, 0].iter().map(|&x| Mod64::from(x)).collect());
+        let expected = [1, 0, 2, 0, 0, 0, 0]
+            .iter()
+            .map(|&x| Mod64::from(x))
+            .collect_vec();
+        assert_eq!(integrate(a, &c).coef, expected);
+    }
+
+    #[test]
+    fn test_coefficient_order() {
+        let v = [1, 2, 3].iter().map(|&x| BigInt::from(x)).collect_vec();
+        let a = Polynomial::from_ascending(v.clone());
+        let b = Polynomial::with_order(v.clone(), CoefficientOrder::Descending);
+        assert_eq!(a.coeff(0), BigInt::from(1));
+        assert_eq!(b.coeff(0), BigInt::from(3));
+        assert_eq!(a.coeff(5), BigInt::from(0));
+        assert_eq!(a.to_ascending(), v);
+        assert_eq!(b.to_vec_in(CoefficientOrder::Descending), v);
+        assert_eq!(a.to_descending(), b.to_ascending());
+        // 1 + 2x + 3x^2 at x = 2
+        assert_eq!(a.evaluate(BigInt::from(2)), BigInt::from(17));
+
+        let mut a = a;
+        a.set_coeff(4, BigInt::from(5));
+        assert_eq!(
+            a.to_ascending(),
+            [1, 2, 3, 0, 5]
+                .iter()
+                .map(|&x| BigInt::from(x))
+                .collect_vec()
+        );
+        a.set_coeff(1, BigInt::from(7));
+        assert_eq!(a.coeff(1), BigInt::from(7));
+    }
+
+    #[test]
+    fn test_mul_auto() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        [
+            (1, 1),
+            (20, 45),
+            (33, 200),
+            (90, 129),
+            (150, 1000),
+            (300, 310),
+        ]
+        .iter()
+        .for_each(|&(x, y)| {
+            let (a, b) = (random_fp(x), random_fp(y));
+            let expected = fast_mul(a.clone(), b.clone(), &c);
+            assert_eq!(mul_auto(&a, &b, &c).coef, expected.coef);
+            assert_eq!(mul_karatsuba(a.clone(), b.clone()).coef, expected.coef);
+            // every algorithm, including Karatsuba down to single terms
+            [(0, 0), (0, usize::MAX), (4, 64), (usize::MAX, usize::MAX)]
+                .iter()
+                .for_each(|&(karatsuba, ntt)| {
+                    let t = MulThresholds { karatsuba, ntt };
+                    assert_eq!(t.mul(&b, &a, &c).coef, expected.coef);
+                });
+        });
+
+        let a = Polynomial::new([3, 2, 1].iter().map(|&x| BigInt::from(x)).collect());
+        assert_eq!(
+            mul_karatsuba(a.clone(), a.clone()).coef,
+            mul_brute(a.clone(), a.clone()).coef[..5]
+        );
+        let empty = Polynomial::new(vec![]);
+        assert_eq!(
+            mul_auto(&empty, &random_fp(3), &c).coef,
+            vec![Fp998244353::from(0)]
+        );
+    }
+
+    #[test]
+    fn test_mul_unbalanced() {
+        let c = Constants::<Fp998244353>::for_size(1 << 14);
+        [(1, 5000), (40, 3000), (100, 401), (200, 5000), (1000, 1000)]
+            .iter()
+            .for_each(|&(x, y)| {
+                let (a, b) = (random_fp(x), random_fp(y));
+                let expected = mul_karatsuba(a.clone(), b.clone());
+                assert_eq!(mul_unbalanced(&a, &b, &c).coef, expected.coef);
+                assert_eq!(mul_unbalanced(&b, &a, &c).coef, expected.coef);
+                assert_eq!(fast_mul(a.clone(), b.clone(), &c).coef, expected.coef);
+                assert_eq!(mul_auto(&b, &a, &c).coef, expected.coef);
+            });
+
+        // only a transform sized for the short operand is needed
+        let small = Constants::<Fp998244353>::for_size(1 << 8);
+        let (a, b) = (random_fp(100), random_fp(20000));
+        assert_eq!(
+            mul_unbalanced(&a, &b, &small).coef,
+            mul_karatsuba(a.clone(), b.clone()).coef
+        );
+
+        let mut padded = random_fp(30);
+        padded.coef.splice(0..0, vec![Fp998244353::from(0); 3]);
+        let b = random_fp(500);
+        assert_eq!(
+            fast_mul(padded.clone(), b.clone(), &c).coef,
+            mul_karatsuba(Polynomial::new(padded.coef[3..].to_vec()), b.clone()).coef
+        );
+        let zero = Polynomial::new(vec![Fp998244353::from(0); 3]);
+        assert_eq!(
+            mul_unbalanced(&zero, &b, &c).coef,
+            vec![Fp998244353::from(0)]
+        );
+        assert_eq!(
+            fast_mul(zero.clone(), b.clone(), &c).coef,
+            vec![Fp998244353::from(0)]
+        );
+        assert_eq!(
+            fast_mul(zero, random_fp(20), &c).coef,
+            vec![Fp998244353::from(0)]
+        );
+    }
+
+    #[test]
+    fn test_gcd() {
+        let c = Constants::<Fp998244353>::for_size(1 << 14);
+        let ONE = Fp998244353::from(1);
+        // sizes on both sides of the half-GCD threshold
+        [(5, 12, 9), (40, 100, 70), (300, 700, 500), (1, 1500, 1200)]
+            .iter()
+            .for_each(|&(x, y, z)| {
+                let f = random_fp(x);
+                let (u, v) = (random_fp(y), random_fp(z));
+                let a = mul_auto(&f, &u, &c);
+                let b = mul_auto(&f, &v, &c);
+                let (g, s, t) = xgcd(&a, &b, &c);
+                // gcd(u, v) = 1 with high probability
+                let lead = f.coef[0].invert();
+                assert_eq!(g.coef, (f.clone() * lead).coef);
+                assert_eq!(gcd(&b, &a, &c).coef, g.coef);
+                let bezout = mul_auto(&s, &a, &c) + mul_auto(&t, &b, &c);
+                assert_eq!(Polynomial::new(trim(bezout.coef)).coef, g.coef);
+                assert!(s.coef.len() <= v.coef.len() && t.coef.len() <= u.coef.len());
+            });
+
+        let zero = Polynomial::new(vec![Fp998244353::from(0)]);
+        let a = random_fp(20);
+        assert_eq!(gcd(&zero, &zero, &c).coef, zero.coef);
+        assert_eq!(gcd(&a, &zero, &c).coef[0], ONE);
+        assert_eq!(gcd(&a, &zero, &c).coef.len(), 20);
+    }
+
+    #[test]
+    fn test_inverse_mod() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        [(30, 17), (200, 500), (700, 300)]
+            .iter()
+            .for_each(|&(x, y)| {
+                let (a, m) = (random_fp(x), random_fp(y));
+                let inv = inverse_mod(&a, &m, &c);
+                assert!(inv.coef.len() < m.coef.len());
+                let (_, r) = div_rem(mul_auto(&a, &inv, &c), m.clone(), &c);
+                assert_eq!(r.coef, vec![Fp998244353::from(1)]);
+            });
+
+        let f = random_fp(10);
+        let a = mul_auto(&f, &random_fp(5), &c);
+        let m = mul_auto(&f, &random_fp(8), &c);
+        assert_eq!(
+            try_inverse_mod(&a, &m, &c).unwrap_err(),
+            NttError::NotInvertible
+        );
+        let zero = Polynomial::new(vec![Fp998244353::from(0)]);
+        assert_eq!(
+            try_inverse_mod(&a, &zero, &c).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+        assert!(inverse_mod(&a, &random_fp(1), &c).coef[0].is_zero());
+    }
+
+    #[test]
+    fn test_pow_mod() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        let (base, m) = (random_fp(150), random_fp(100));
+        let mut expected = Polynomial::new(vec![Fp998244353::from(1)]);
+        (0..13).for_each(|_| {
+            expected = div_rem(mul_auto(&expected, &base, &c), m.clone(), &c).1;
+        });
+        assert_eq!(pow_mod(&base, BigInt::from(13), &m, &c).coef, expected.coef);
+
+        // (b^(2^100))^(2^100) = b^(2^200)
+        let e = BigInt::from(1) << 100;
+        let half = pow_mod(&base, e, &m, &c);
+        assert_eq!(
+            pow_mod(&half, e, &m, &c).coef,
+            pow_mod(&base, BigInt::from(1) << 200, &m, &c).coef
+        );
+        assert_eq!(
+            pow_mod(&base, BigInt::from(0), &m, &c).coef,
+            vec![Fp998244353::from(1)]
+        );
+        assert!(pow_mod(&base, BigInt::from(5), &random_fp(1), &c).coef[0].is_zero());
+        let zero = Polynomial::new(vec![Fp998244353::from(0)]);
+        assert_eq!(
+            try_pow_mod(&base, BigInt::from(5), &zero, &c).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+    }
+
+    #[test]
+    fn test_compose_mod() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        [(1, 10, 5), (7, 3, 40), (50, 120, 90), (200, 40, 60)]
+            .iter()
+            .for_each(|&(x, y, z)| {
+                let (f, g, h) = (random_fp(x), random_fp(y), random_fp(z));
+                // Horner's rule, one product modulo `h` per coefficient
+                let expected =
+                    f.coef
+                        .iter()
+                        .fold(Polynomial::new(vec![Fp998244353::from(0)]), |acc, &a| {
+                            let prod = mul_auto(&acc, &g, &c) + Polynomial::new(vec![a]);
+                            div_rem(prod, h.clone(), &c).1
+                        });
+                assert_eq!(compose_mod(&f, &g, &h, &c).coef, expected.coef);
+            });
+    }
+
+    #[test]
+    fn test_operators() {
+        let c = Constants::<Fp998244353>::for_size(1 << 10);
+        let a = random_fp(100);
+        let b = random_fp(60);
+        let expected = fast_mul(a.clone(), b.clone(), &c);
+
+        let (ma, mb) = (
+            ModPolynomial::from_polynomial(a.clone(), &c),
+            ModPolynomial::from_polynomial(b.clone(), &c),
+        );
+        assert_eq!((&ma * &mb).coef, expected.coef);
+        let mut prod = ma.clone();
+        prod *= &mb;
+        assert_eq!(prod.coef, expected.coef);
+        // short operands take the schoolbook path
+        let short = ModPolynomial::from_polynomial(random_fp(3), &c);
+        let expected = mul_brute(a.clone(), short.to_polynomial());
+        assert_eq!((ma.clone() * short).coef, expected.coef[..102]);
+
+        let two = Fp998244353::from(2);
+        assert_eq!((&ma + &ma).coef, (&ma * two).coef);
+        assert_eq!((ma.clone() - ma.clone()).coef, vec![Fp998244353::from(0)]);
+        let mut m = ma.clone();
+        m += &mb;
+        m -= mb.clone();
+        m *= two;
+        assert_eq!(m.coef, (a.clone() * two).coef);
+
+        assert_eq!((&a + &b).coef, (a.clone() + b.clone()).coef);
+        assert_eq!((&a - &a).coef, vec![Fp998244353::from(0); 100]);
+        assert_eq!((-&a).coef, (-a.clone()).coef);
+        let mut s = a.clone();
+        s += b.clone();
+        s -= b;
+        s *= two;
+        assert_eq!(s.coef, (&a * two).coef);
+    }
+
+    #[test]
+    fn test_errors() {
+        let ZERO = Fp998244353::from(0);
+        let ONE = Fp998244353::from(1);
+        let c = Constants::<Fp998244353>::for_size(1 << 4);
+        let zero = Polynomial::new(vec![ZERO; 3]);
+        let one = Polynomial::new(vec![ONE]);
+
+        assert_eq!(zero.try_degree().unwrap_err(), NttError::ZeroPolynomial);
+        assert_eq!(
+            try_div_rem(one.clone(), zero.clone(), &c).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+        assert_eq!(try_diff(one.clone()).unwrap().coef, vec![ZERO]);
+        assert_eq!(
+            try_diff(Polynomial::<Fp998244353>::new(vec![])).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+
+        let plan = NttPlan::new(&c, 1 << 2);
+        assert_eq!(
+            try_fast_mul_with_plan(zero.clone(), zero, &plan).unwrap_err(),
+            NttError::LengthMismatch {
+                expected: 4,
+                found: 6
+            }
+        );
+        assert_eq!(
+            RingPolynomial::try_new(vec![ONE; 3], &c).unwrap_err(),
+            NttError::NonPowerOfTwoLength(3)
+        );
+        let a = RingPolynomial::new(vec![ONE; 4], &c);
+        let b = RingPolynomial::new(vec![ONE; 8], &c);
+        assert_eq!(
+            a.clone().try_mul(b).unwrap_err(),
+            NttError::LengthMismatch {
+                expected: 4,
+                found: 8
+            }
+        );
+        let other = Constants::<Fp998244353>::for_size(1 << 5);
+        let b = RingPolynomial::new(vec![ONE; 4], &other);
+        assert_eq!(a.try_mul(b).unwrap_err(), NttError::ConstantsMismatch);
+
+        let a = ModPolynomial::new(vec![ONE; 4], &c);
+        let b = ModPolynomial::new(vec![ONE; 4], &other);
+        assert_eq!(a.try_mul(&b).unwrap_err(), NttError::ConstantsMismatch);
+        assert_eq!(a.try_add(&b).unwrap_err(), NttError::ConstantsMismatch);
+        assert_eq!(a.try_sub(&b).unwrap_err(), NttError::ConstantsMismatch);
+        assert!(!a.is_empty());
     }
 
     #[test]
@@ -0,0 +1,177 @@
+use crate::{
+    error::NttError,
+    ntt::Constants,
+    numbers::BigInt,
+    polynomial::{pow_mod_stripped, strip, Polynomial, PolynomialFieldElement},
+};
+
+/// Berlekamp-Massey over the prime field `Z_N`: the shortest `c_1, ..., c_L`
+/// with `s[i] = c_1 s[i - 1] + ... + c_L s[i - L]` for every `i >= L` in
+/// `s`. At least `2L` terms are needed to pin down a recurrence of order `L`.
+pub fn berlekamp_massey<T: PolynomialFieldElement>(s: &[T], c: &Constants<T>) -> Vec<T> {
+    let ZERO = c.reduce(T::from(0));
+    let ONE = c.reduce(T::from(1));
+    let s: Vec<T> = s.iter().map(|&x| c.reduce(x)).collect();
+
+    // connection polynomials 1 + C_1 x + ..., current and before the last
+    // length change, ascending
+    let mut cur = vec![ONE];
+    let mut prev = vec![ONE];
+    let mut len = 0;
+    let mut shift = 1;
+    let mut prev_disc = ONE;
+    for n in 0..s.len() {
+        let disc = (1..=len).fold(s[n], |acc, i| acc + cur[i] * s[n - i]);
+        if disc.is_zero() {
+            shift += 1;
+            continue;
+        }
+        let coef = disc * prev_disc.invert();
+        let next = cur.clone();
+        if cur.len() < prev.len() + shift {
+            cur.resize(prev.len() + shift, ZERO);
+        }
+        prev.iter()
+            .enumerate()
+            .for_each(|(i, &x)| cur[i + shift] = cur[i + shift] - coef * x);
+        if 2 * len <= n {
+            len = n + 1 - len;
+            if cur.len() <= len {
+                cur.resize(len + 1, ZERO);
+            }
+            prev = next;
+            prev_disc = disc;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+    cur.resize(len + 1, ZERO);
+    cur[1..].iter().map(|&x| -x).collect()
+}
+
+/// Minimal polynomial `x^L - c_1 x^(L-1) - ... - c_L` of the recurrence
+/// that `berlekamp_massey` finds for `s`.
+pub fn minimal_polynomial<T: PolynomialFieldElement>(s: &[T], c: &Constants<T>) -> Polynomial<T> {
+    let rec = berlekamp_massey(s, c);
+    Polynomial::new(
+        std::iter::once(c.reduce(T::from(1)))
+            .chain(rec.into_iter().map(|x| -x))
+            .collect(),
+    )
+}
+
+/// Term `n` of the sequence starting `init` and continuing by
+/// `s[i] = rec[0] s[i - 1] + ... + rec[L - 1] s[i - L]`, with Fiduccia's
+/// algorithm: `x^n` modulo the minimal polynomial, through `pow_mod`, gives
+/// the combination of the first `L` terms equal to term `n`.
+pub fn nth_term<T: PolynomialFieldElement>(init: &[T], rec: &[T], n: u64, c: &Constants<T>) -> T {
+    try_nth_term(init, rec, n, c).unwrap()
+}
+
+pub fn try_nth_term<T: PolynomialFieldElement>(
+    init: &[T],
+    rec: &[T],
+    n: u64,
+    c: &Constants<T>,
+) -> Result<T, NttError> {
+    let ZERO = c.reduce(T::from(0));
+    let ONE = c.reduce(T::from(1));
+    let l = rec.len();
+    if init.len() < l {
+        return Err(NttError::LengthMismatch {
+            expected: l,
+            found: init.len(),
+        });
+    }
+    if n < init.len() as u64 {
+        return Ok(c.reduce(init[n as usize]));
+    }
+    if l == 0 {
+        return Ok(ZERO);
+    }
+
+    let q: Vec<T> = std::iter::once(ONE)
+        .chain(rec.iter().map(|&x| -c.reduce(x)))
+        .collect();
+    let r = strip(pow_mod_stripped(&[ONE, ZERO], BigInt::from(n), &q, c)?);
+    // r holds the coefficients of x^(L - 1) down to x^0, read upwards
+    Ok(r.iter()
+        .rev()
+        .zip(init)
+        .fold(ZERO, |acc, (&x, &a)| acc + x * c.reduce(a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::{berlekamp_massey, minimal_polynomial, nth_term, try_nth_term};
+    use crate::{error::NttError, ntt::Constants, numbers::Fp998244353};
+
+    fn fp(x: u64) -> Fp998244353 {
+        Fp998244353::from(x)
+    }
+
+    // Fibonacci numbers by fast doubling, modulo 998244353
+    fn fib(n: u64) -> u64 {
+        const P: u64 = 998244353;
+        if n == 0 {
+            return 0;
+        }
+        let (mut a, mut b) = (0_u64, 1_u64);
+        (0..64 - n.leading_zeros()).rev().for_each(|i| {
+            let c = a * ((2 * b + P - a) % P) % P;
+            let d = (a * a + b * b) % P;
+            (a, b) = if (n >> i) & 1 == 1 {
+                (d, (c + d) % P)
+            } else {
+                (c, d)
+            };
+        });
+        a
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let c = Constants::<Fp998244353>::for_size(1 << 4);
+        let s: Vec<Fp998244353> = (0..20).map(|n| fp(fib(n))).collect();
+        assert_eq!(berlekamp_massey(&s, &c), vec![fp(1), fp(1)]);
+        assert_eq!(minimal_polynomial(&s, &c).coef, vec![fp(1), -fp(1), -fp(1)]);
+        [5, 100, 1_000_000_007, 1_000_000_000_000_000_000]
+            .iter()
+            .for_each(|&n| assert_eq!(nth_term(&s[..2], &[fp(1), fp(1)], n, &c), fp(fib(n))));
+    }
+
+    #[test]
+    fn test_random_recurrence() {
+        let c = Constants::<Fp998244353>::for_size(1 << 10);
+        let l = 60;
+        let rand_fp = || fp(rand::thread_rng().gen::<u64>() % 998244353);
+        let rec: Vec<Fp998244353> = (0..l).map(|_| rand_fp()).collect();
+        let mut s: Vec<Fp998244353> = (0..l).map(|_| rand_fp()).collect();
+        (l..300).for_each(|i| {
+            let next = (0..l).fold(fp(0), |acc, j| acc + rec[j] * s[i - 1 - j]);
+            s.push(next);
+        });
+        assert_eq!(berlekamp_massey(&s, &c), rec);
+        assert_eq!(nth_term(&s, &rec, 250, &c), s[250]);
+        assert_eq!(nth_term(&s[..l], &rec, 299, &c), s[299]);
+
+        // the recurrence still holds far out
+        let n = 1_000_000_000_000_u64;
+        let expected = (0..l).fold(fp(0), |acc, j| {
+            acc + rec[j] * nth_term(&s[..l], &rec, n - 1 - j as u64, &c)
+        });
+        assert_eq!(nth_term(&s[..l], &rec, n, &c), expected);
+
+        assert!(berlekamp_massey(&[fp(0); 10], &c).is_empty());
+        assert_eq!(
+            try_nth_term(&s[..3], &rec, 10, &c).unwrap_err(),
+            NttError::LengthMismatch {
+                expected: l,
+                found: 3
+            }
+        );
+    }
+}
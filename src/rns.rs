@@ -0,0 +1,186 @@
+use crypto_bigint::Invert;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    error::NttError,
+    ntt::{two_adic_root, Constants},
+    numbers::{BigInt, Mod64},
+    polynomial::{convolve, trim, Polynomial},
+};
+
+/// Primes `c * 2^k + 1` just below 2^62 with `k >= 40`, so each supports
+/// transforms of up to 2^40 points.
+pub const RNS_PRIMES: [u64; 5] = [
+    4611615649683210241,
+    4611613450659954689,
+    4611549678985543681,
+    4611546380450660353,
+    4611524390218104833,
+];
+
+// every prime exceeds 2^61
+const PRIME_BITS: u32 = 61;
+
+// exact results must stay below the default `BigInt` modulus
+const MAX_BITS: u32 = 255;
+
+fn residues_mod(coef: &[BigInt], p: u64) -> Vec<Mod64> {
+    let P = BigInt::from(p);
+    coef.iter()
+        .map(|x| Mod64::from(x.rem(P).to_u64().unwrap()))
+        .collect()
+}
+
+// product of `lhs` and `rhs` modulo `p`, as plain residues
+fn product_mod(lhs: &[BigInt], rhs: &[BigInt], p: u64, n: usize) -> Result<Vec<u64>, NttError> {
+    let N = Mod64::from(p);
+    let c = Constants {
+        N,
+        w: two_adic_root(N, n)?,
+    };
+    Ok(convolve(&residues_mod(lhs, p), &residues_mod(rhs, p), &c)
+        .iter()
+        .map(|x| x.v)
+        .collect())
+}
+
+#[cfg(feature = "parallel")]
+fn products(
+    lhs: &[BigInt],
+    rhs: &[BigInt],
+    primes: &[u64],
+    n: usize,
+) -> Result<Vec<Vec<u64>>, NttError> {
+    primes
+        .par_iter()
+        .map(|&p| product_mod(lhs, rhs, p, n))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn products(
+    lhs: &[BigInt],
+    rhs: &[BigInt],
+    primes: &[u64],
+    n: usize,
+) -> Result<Vec<Vec<u64>>, NttError> {
+    primes
+        .iter()
+        .map(|&p| product_mod(lhs, rhs, p, n))
+        .collect()
+}
+
+/// Garner's algorithm: the unique `x < p_0 ... p_{k-1}` with
+/// `x = r_i mod p_i`, given one residue per prime.
+pub fn crt(residues: &[u64], primes: &[u64]) -> BigInt {
+    assert_eq!(residues.len(), primes.len());
+    // mixed-radix digits, x = v_0 + v_1 p_0 + v_2 p_0 p_1 + ...
+    let mut digits: Vec<u64> = Vec::with_capacity(primes.len());
+    primes.iter().zip(residues).for_each(|(&p, &r)| {
+        let mut acc = Mod64::new(0, p);
+        let mut radix = Mod64::new(1, p);
+        digits.iter().zip(primes).for_each(|(&v, &q)| {
+            acc += radix * Mod64::from(v);
+            radix *= Mod64::from(q);
+        });
+        digits.push(((Mod64::new(r, p) - acc) * radix.invert()).v);
+    });
+
+    let mut res = BigInt::from(0);
+    let mut radix = BigInt::from(1);
+    digits.iter().zip(primes).for_each(|(&v, &p)| {
+        res += radix * BigInt::from(v);
+        radix *= BigInt::from(p);
+    });
+    res
+}
+
+/// Exact product of polynomials with non-negative integer coefficients,
+/// computed modulo as many of `RNS_PRIMES` as the coefficient bound needs
+/// and reconstructed with the CRT. Every product coefficient is below
+/// `max(lhs) * max(rhs) * min(lhs.len(), rhs.len())`, which must fit in 255
+/// bits.
+pub fn exact_mul(lhs: &Polynomial<BigInt>, rhs: &Polynomial<BigInt>) -> Polynomial<BigInt> {
+    try_exact_mul(lhs, rhs).unwrap()
+}
+
+pub fn try_exact_mul(
+    lhs: &Polynomial<BigInt>,
+    rhs: &Polynomial<BigInt>,
+) -> Result<Polynomial<BigInt>, NttError> {
+    if lhs.coef.is_empty() || rhs.coef.is_empty() {
+        return Ok(Polynomial::new(vec![BigInt::from(0)]));
+    }
+    let max_bits = |x: &[BigInt]| x.iter().map(|x| x.bits()).max().unwrap();
+    let len = lhs.coef.len().min(rhs.coef.len());
+    let bits = max_bits(&lhs.coef) + max_bits(&rhs.coef) + usize::BITS - len.leading_zeros();
+    if bits > MAX_BITS {
+        return Err(NttError::Overflow);
+    }
+    let primes = &RNS_PRIMES[..(bits.div_ceil(PRIME_BITS) as usize).max(1)];
+    let n = (lhs.coef.len() + rhs.coef.len()).next_power_of_two();
+
+    let res = products(&lhs.coef, &rhs.coef, primes, n)?;
+    let coef = (0..res[0].len())
+        .map(|i| crt(&res.iter().map(|r| r[i]).collect::<Vec<_>>(), primes))
+        .collect();
+    Ok(Polynomial::new(trim(coef)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::{crt, exact_mul, try_exact_mul, RNS_PRIMES};
+    use crate::{
+        error::NttError,
+        numbers::{BigInt, BigIntType},
+        polynomial::{mul_brute, Polynomial},
+    };
+
+    fn random_poly(n: usize) -> Polynomial<BigInt> {
+        Polynomial::new(
+            (0..n)
+                .map(|_| BigInt::new(BigIntType::U128(rand::thread_rng().gen::<u128>() >> 20)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_crt() {
+        let x: u128 = rand::thread_rng().gen();
+        let residues: Vec<u64> = RNS_PRIMES[..3]
+            .iter()
+            .map(|&p| (x % p as u128) as u64)
+            .collect();
+        assert_eq!(crt(&residues, &RNS_PRIMES[..3]), BigInt::from(x));
+    }
+
+    #[test]
+    fn test_exact_mul() {
+        (1..6).for_each(|_| {
+            let a = random_poly(rand::thread_rng().gen::<usize>() % 80 + 1);
+            let b = random_poly(rand::thread_rng().gen::<usize>() % 80 + 1);
+            let len = a.coef.len() + b.coef.len() - 1;
+            let expected = mul_brute(a.clone(), b.clone());
+            assert_eq!(exact_mul(&a, &b).coef, expected.coef[..len]);
+        });
+
+        let small = Polynomial::new([1, 2, 3].iter().map(|&x| BigInt::from(x)).collect());
+        let prod = exact_mul(&small, &small);
+        assert_eq!(
+            prod.coef,
+            [1, 4, 10, 12, 9]
+                .iter()
+                .map(|&x| BigInt::from(x))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_exact_mul_overflow() {
+        let big = Polynomial::new(vec![BigInt::from(1) << 200; 4]);
+        assert_eq!(try_exact_mul(&big, &big).unwrap_err(), NttError::Overflow);
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{
+    error::NttError,
+    ntt::Constants,
+    polynomial::{
+        antiderivative, inv_series, trim, try_convolve, Polynomial, PolynomialFieldElement,
+    },
+};
+
+// first `n` ascending coefficients of `f`, lifted into `Z_N`
+fn ascending<T: PolynomialFieldElement>(f: &Polynomial<T>, n: usize, c: &Constants<T>) -> Vec<T> {
+    let mut v: Vec<T> = f.coef.iter().rev().take(n).map(|&x| c.reduce(x)).collect();
+    v.resize(n, c.reduce(T::from(0)));
+    v
+}
+
+fn descending<T: PolynomialFieldElement>(v: Vec<T>) -> Polynomial<T> {
+    Polynomial::new(trim(v.into_iter().rev().collect()))
+}
+
+// `a * b mod x^n`, padded to exactly `n` terms
+fn mul_trunc<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let mut res = try_convolve(a, b, c)?;
+    res.resize(n, c.reduce(T::from(0)));
+    Ok(res)
+}
+
+// `log f mod x^n = integral(f' / f)` for lifted `f` of `n` terms with `f[0] = 1`
+fn log_asc<T: PolynomialFieldElement>(
+    f: &[T],
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    if n <= 1 {
+        return Ok(vec![c.reduce(T::from(0)); n]);
+    }
+    let df: Vec<T> = (1..n).map(|i| f[i] * c.reduce(T::from(i))).collect();
+    let q = mul_trunc(&df, &inv_series(f, n, c)?, n - 1, c)?;
+    antiderivative(&q, c)
+}
+
+// Newton iteration `g = g * (1 - log g + f)` for lifted `f` with `f[0] = 0`
+fn exp_asc<T: PolynomialFieldElement>(
+    f: &[T],
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let ONE = c.reduce(T::from(1));
+    let mut g = vec![ONE];
+    while g.len() < n {
+        let m = (2 * g.len()).min(n);
+        g.resize(m, c.reduce(T::from(0)));
+        let lg = log_asc(&g, m, c)?;
+        let mut e: Vec<T> = f[..m].iter().zip(lg).map(|(&x, y)| x - y).collect();
+        e[0] += ONE;
+        g = mul_trunc(&g, &e, m, c)?;
+    }
+    g.truncate(n);
+    Ok(g)
+}
+
+// Tonelli-Shanks square root of lifted `a` modulo the prime `c.N`
+fn sqrt_mod<T: PolynomialFieldElement>(a: T, c: &Constants<T>) -> Option<T> {
+    if a.is_zero() {
+        return Some(a);
+    }
+    let ONE = c.reduce(T::from(1));
+    let TWO = T::from(2);
+    let half = (c.N - T::from(1)) / TWO;
+    if a.mod_exp(half, c.N) != ONE {
+        return None;
+    }
+
+    // N - 1 = q 2^s with q odd
+    let mut q = c.N - T::from(1);
+    let mut s = 0;
+    while q.is_even() {
+        q >>= 1;
+        s += 1;
+    }
+    let mut z = c.reduce(TWO);
+    while z.mod_exp(half, c.N) == ONE {
+        z += ONE;
+    }
+
+    let mut b = z.mod_exp(q, c.N);
+    let mut t = a.mod_exp(q, c.N);
+    let mut r = a.mod_exp((q + T::from(1)) / TWO, c.N);
+    while t != ONE {
+        // least `i` with t^(2^i) = 1
+        let mut i = 0;
+        let mut tt = t;
+        while tt != ONE {
+            tt = tt * tt;
+            i += 1;
+        }
+        (0..s - i - 1).for_each(|_| b = b * b);
+        s = i;
+        r *= b;
+        b = b * b;
+        t *= b;
+    }
+    Some(r)
+}
+
+/// `1 / f mod x^n` by Newton iteration. `f` must have an invertible
+/// constant term, and here and below `c.w` must have a power-of-two order
+/// of at least `2n`.
+pub fn inv_mod_xn<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_inv_mod_xn(f, n, c).unwrap()
+}
+
+pub fn try_inv_mod_xn<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    if n == 0 {
+        return Ok(descending(vec![]));
+    }
+    let f = ascending(f, n, c);
+    if f[0].is_zero() {
+        return Err(NttError::NotInvertible);
+    }
+    Ok(descending(inv_series(&f, n, c)?))
+}
+
+/// `log f mod x^n` for `f` with constant term 1. Like `exp` and `pow`, it
+/// integrates, failing with `CharacteristicTooSmall` unless `N > n - 1`.
+pub fn log<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_log(f, n, c).unwrap()
+}
+
+pub fn try_log<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let f = ascending(f, n.max(1), c);
+    if f[0] != c.reduce(T::from(1)) {
+        return Err(NttError::InvalidConstantTerm);
+    }
+    Ok(descending(log_asc(&f, n, c)?))
+}
+
+/// `exp f mod x^n` for `f` with constant term 0.
+pub fn exp<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_exp(f, n, c).unwrap()
+}
+
+pub fn try_exp<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let f = ascending(f, n.max(1), c);
+    if !f[0].is_zero() {
+        return Err(NttError::InvalidConstantTerm);
+    }
+    Ok(descending(exp_asc(&f, n, c)?))
+}
+
+/// A square root of `f` modulo `x^n`. The lowest nonzero term of `f` must
+/// have even degree and a coefficient that is a square modulo `N`.
+pub fn sqrt<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_sqrt(f, n, c).unwrap()
+}
+
+pub fn try_sqrt<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let f = ascending(f, n + f.coef.len(), c);
+    let z = match f.iter().position(|x| !x.is_zero()) {
+        Some(z) if z / 2 < n => z,
+        _ => return Ok(descending(vec![])),
+    };
+    if z % 2 == 1 {
+        return Err(NttError::InvalidConstantTerm);
+    }
+    let m = n - z / 2;
+    let h = &f[z..z + m];
+
+    let ZERO = c.reduce(T::from(0));
+    let half = c.reduce(T::from(2)).invert();
+    let mut g = vec![sqrt_mod(h[0], c).ok_or(NttError::InvalidConstantTerm)?];
+    // g = (g + h / g) / 2
+    while g.len() < m {
+        let t = (2 * g.len()).min(m);
+        g.resize(t, ZERO);
+        let q = mul_trunc(&h[..t], &inv_series(&g, t, c)?, t, c)?;
+        g = g.iter().zip(q).map(|(&x, y)| (x + y) * half).collect();
+    }
+
+    let mut res = vec![ZERO; z / 2];
+    res.extend(g);
+    Ok(descending(res))
+}
+
+/// `f^k mod x^n`, via `exp(k log f)` after factoring out the lowest term.
+pub fn pow<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    k: usize,
+    n: usize,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_pow(f, k, n, c).unwrap()
+}
+
+pub fn try_pow<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    k: usize,
+    n: usize,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let ZERO = c.reduce(T::from(0));
+    if n == 0 {
+        return Ok(descending(vec![]));
+    }
+    if k == 0 {
+        return Ok(descending(vec![c.reduce(T::from(1))]));
+    }
+    let f = ascending(f, n, c);
+    let z = match f.iter().position(|x| !x.is_zero()) {
+        Some(z) => z,
+        None => return Ok(descending(vec![])),
+    };
+    let shift = match z.checked_mul(k) {
+        Some(shift) if shift < n => shift,
+        _ => return Ok(descending(vec![])),
+    };
+    let m = n - shift;
+
+    let a = f[z];
+    let a_inv = a.invert();
+    let g: Vec<T> = f[z..z + m].iter().map(|&x| x * a_inv).collect();
+    let K = c.reduce(T::from(k));
+    let lg: Vec<T> = log_asc(&g, m, c)?.iter().map(|&x| x * K).collect();
+    let ak = a.mod_exp(T::from(k), c.N);
+
+    let mut res = vec![ZERO; shift];
+    res.extend(exp_asc(&lg, m, c)?.iter().map(|&x| x * ak));
+    Ok(descending(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::Invert;
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use crate::{
+        error::NttError,
+        ntt::Constants,
+        numbers::{BigInt, Fp998244353},
+        polynomial::{fast_mul, trim, Polynomial},
+        series::{exp, inv_mod_xn, log, pow, sqrt, try_exp, try_inv_mod_xn, try_log, try_sqrt},
+    };
+
+    fn random_series(n: usize, constant: u32) -> Polynomial<Fp998244353> {
+        let mut coef = (0..n)
+            .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+            .collect_vec();
+        coef[n - 1] = Fp998244353::from(constant);
+        Polynomial::new(coef)
+    }
+
+    // `f mod x^n`, trimmed like the series results
+    fn truncate(f: &Polynomial<Fp998244353>, n: usize) -> Vec<Fp998244353> {
+        trim(f.coef[f.coef.len().saturating_sub(n)..].to_vec())
+    }
+
+    #[test]
+    fn test_inv_mod_xn() {
+        let c = Constants::<Fp998244353>::for_size(1 << 10);
+        [1, 7, 64, 300].iter().for_each(|&n| {
+            let f = random_series(n + 5, 3);
+            let g = inv_mod_xn(&f, n, &c);
+            let one = truncate(&fast_mul(f, g, &c), n);
+            assert_eq!(one, vec![Fp998244353::from(1)]);
+        });
+        let f = Polynomial::new(vec![Fp998244353::from(1), Fp998244353::from(0)]);
+        assert_eq!(
+            try_inv_mod_xn(&f, 4, &c).unwrap_err(),
+            NttError::NotInvertible
+        );
+    }
+
+    #[test]
+    fn test_log_exp() {
+        let c = Constants::<Fp998244353>::for_size(1 << 10);
+        [1, 2, 33, 200].iter().for_each(|&n| {
+            let f = random_series(n, 1);
+            assert_eq!(exp(&log(&f, n, &c), n, &c).coef, truncate(&f, n));
+            let g = random_series(n, 0);
+            assert_eq!(log(&exp(&g, n, &c), n, &c).coef, truncate(&g, n));
+        });
+
+        // log(1 / (1 - x)) = sum x^k / k
+        let f = inv_mod_xn(
+            &Polynomial::new(vec![-Fp998244353::from(1), Fp998244353::from(1)]),
+            6,
+            &c,
+        );
+        let expected = (1..6)
+            .rev()
+            .map(|k| Fp998244353::from(k).invert())
+            .collect_vec();
+        assert_eq!(log(&f, 6, &c).coef[..5], expected);
+
+        let g = random_series(4, 2);
+        assert_eq!(
+            try_log(&g, 4, &c).unwrap_err(),
+            NttError::InvalidConstantTerm
+        );
+        assert_eq!(
+            try_exp(&g, 4, &c).unwrap_err(),
+            NttError::InvalidConstantTerm
+        );
+    }
+
+    #[test]
+    fn test_sqrt() {
+        let c = Constants::<Fp998244353>::for_size(1 << 10);
+        [1, 16, 100].iter().for_each(|&n| {
+            let g = random_series(n, 5);
+            let f = fast_mul(g.clone(), g.clone(), &c);
+            let h = sqrt(&f, n, &c);
+            assert_eq!(truncate(&fast_mul(h.clone(), h, &c), n), truncate(&f, n));
+
+            // shifted by x^4
+            let mut coef = f.coef.clone();
+            coef.extend([Fp998244353::from(0); 4]);
+            let h = sqrt(&Polynomial::new(coef.clone()), n + 2, &c);
+            let sq = fast_mul(h.clone(), h, &c);
+            assert_eq!(
+                truncate(&sq, n + 2),
+                truncate(&Polynomial::new(coef), n + 2)
+            );
+        });
+
+        // 3 is not a square modulo 998244353
+        let f = random_series(5, 3);
+        assert_eq!(
+            try_sqrt(&f, 5, &c).unwrap_err(),
+            NttError::InvalidConstantTerm
+        );
+        let mut coef = random_series(5, 1).coef;
+        coef.push(Fp998244353::from(0));
+        assert_eq!(
+            try_sqrt(&Polynomial::new(coef), 5, &c).unwrap_err(),
+            NttError::InvalidConstantTerm
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        let c = Constants::<Fp998244353>::for_size(1 << 10);
+        let n = 50;
+        let mut coef = random_series(20, 7).coef;
+        coef.extend([Fp998244353::from(0); 2]);
+        let f = Polynomial::new(coef);
+        let mut expected = Polynomial::new(vec![Fp998244353::from(1)]);
+        (0..=5).for_each(|k| {
+            assert_eq!(pow(&f, k, n, &c).coef, truncate(&expected, n));
+            expected = fast_mul(expected.clone(), f.clone(), &c);
+        });
+        assert_eq!(pow(&f, 30, n, &c).coef, vec![Fp998244353::from(0)]);
+    }
+
+    #[test]
+    fn test_bigint() {
+        let N = BigInt::from(998244353);
+        let c = Constants {
+            N,
+            w: BigInt::from(15311432),
+        };
+        let f = Polynomial::new([3, 1, 4, 1].iter().map(|&x| BigInt::from(x)).collect());
+        let g = exp(&log(&f, 4, &c), 4, &c);
+        assert_eq!(g.coef, f.coef);
+    }
+}
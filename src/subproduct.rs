@@ -0,0 +1,218 @@
+use crate::{
+    ntt::{inverse, Constants},
+    polynomial::{convolve, diff, div_rem, trim, Polynomial, PolynomialFieldElement},
+};
+
+/// Products of `x - x_i` over a set of points, built pairwise from the
+/// leaves up. `levels[0]` holds the linear factors and the last level holds
+/// the vanishing polynomial of all points.
+#[derive(Debug, Clone)]
+pub struct SubproductTree<T: PolynomialFieldElement> {
+    pub levels: Vec<Vec<Polynomial<T>>>,
+}
+
+impl<T: PolynomialFieldElement> SubproductTree<T> {
+    pub fn new(points: &[T], c: &Constants<T>) -> Self {
+        assert!(!points.is_empty());
+        let ONE = c.reduce(T::from(1));
+        let leaves = points
+            .iter()
+            .map(|&x| Polynomial::new(vec![ONE, -c.reduce(x)]))
+            .collect();
+
+        let mut levels: Vec<Vec<Polynomial<T>>> = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => Polynomial::new(convolve(&a.coef, &b.coef, c)),
+                    [a] => a.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        SubproductTree { levels }
+    }
+
+    pub fn root(&self) -> &Polynomial<T> {
+        &self.levels.last().unwrap()[0]
+    }
+
+    /// Evaluates `f` at every point of the tree by reducing it down the
+    /// remainder tree.
+    pub fn evaluate(&self, f: &Polynomial<T>, c: &Constants<T>) -> Vec<T> {
+        let mut rems = vec![div_rem(f.clone(), self.root().clone(), c).1];
+        for level in self.levels.iter().rev().skip(1) {
+            rems = level
+                .iter()
+                .enumerate()
+                .map(|(i, node)| div_rem(rems[i / 2].clone(), node.clone(), c).1)
+                .collect();
+        }
+        rems.iter().map(|r| r[r.coef.len() - 1]).collect()
+    }
+
+    /// Combines per-point weights `w_i` into `sum_i w_i * M(x) / (x - x_i)`.
+    fn linear_combination(&self, weights: &[T], c: &Constants<T>) -> Polynomial<T> {
+        let mut polys: Vec<Polynomial<T>> =
+            weights.iter().map(|&w| Polynomial::new(vec![w])).collect();
+        for level in self.levels.iter().take(self.levels.len() - 1) {
+            polys = polys
+                .chunks(2)
+                .zip(level.chunks(2))
+                .map(|(p, m)| match (p, m) {
+                    ([l, r], [ml, mr]) => {
+                        Polynomial::new(convolve(&l.coef, &mr.coef, c))
+                            + Polynomial::new(convolve(&r.coef, &ml.coef, c))
+                    }
+                    ([l], [_]) => l.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        Polynomial::new(trim(polys[0].coef.clone()))
+    }
+}
+
+// `Some(w)` if `points` are `1, w, w^2, ..., w^(n-1)` for a primitive
+// power-of-two root of unity `w`
+fn root_of_unity_points<T: PolynomialFieldElement>(points: &[T], c: &Constants<T>) -> Option<T> {
+    let n = points.len();
+    if n < 2 || !n.is_power_of_two() {
+        return None;
+    }
+    let ONE = c.reduce(T::from(1));
+    let w = c.reduce(points[1]);
+    let mut x = ONE;
+    for &p in points {
+        if c.reduce(p) != x {
+            return None;
+        }
+        x *= w;
+    }
+    if x == ONE {
+        Some(w)
+    } else {
+        None
+    }
+}
+
+/// Polynomial of degree below `points.len()` through the distinct
+/// `(points[i], values[i])`, via the subproduct tree and the derivative of
+/// the vanishing polynomial. Points that are the successive powers of a
+/// root of unity are interpolated with a single inverse NTT instead.
+pub fn interpolate<T: PolynomialFieldElement>(
+    points: &[T],
+    values: &[T],
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    assert_eq!(points.len(), values.len());
+    if points.is_empty() {
+        return Polynomial::new(vec![T::from(0)]);
+    }
+    if let Some(w) = root_of_unity_points(points, c) {
+        let mut coef = inverse(values.to_vec(), &Constants { N: c.N, w });
+        coef.reverse();
+        return Polynomial::new(trim(coef));
+    }
+
+    let tree = SubproductTree::new(points, c);
+    let derivative = tree.evaluate(&diff(tree.root().clone()), c);
+    let weights: Vec<T> = values
+        .iter()
+        .zip(derivative)
+        .map(|(&y, d)| c.reduce(y) * d.invert())
+        .collect();
+    tree.linear_combination(&weights, c)
+}
+
+/// Evaluates `f` at all of `points` in `O(M(n) log n)`. `c.w` must have a
+/// power-of-two order of at least `2 * max(f.len(), points.len())`.
+pub fn evaluate_many<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    points: &[T],
+    c: &Constants<T>,
+) -> Vec<T> {
+    if points.is_empty() {
+        return vec![];
+    }
+    SubproductTree::new(points, c).evaluate(f, c)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use crate::{
+        ntt::Constants,
+        numbers::Fp998244353,
+        polynomial::Polynomial,
+        subproduct::{evaluate_many, interpolate, SubproductTree},
+    };
+
+    #[test]
+    fn test_evaluate_many() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        [(100, 150), (200, 7), (1, 5)].iter().for_each(|&(n, m)| {
+            let f = Polynomial::new(
+                (0..n)
+                    .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                    .collect_vec(),
+            );
+            let points = (0..m)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                .collect_vec();
+            let expected = points.iter().map(|&x| f.evaluate(x)).collect_vec();
+            assert_eq!(evaluate_many(&f, &points, &c), expected);
+        });
+    }
+
+    #[test]
+    fn test_tree_root() {
+        let c = Constants::<Fp998244353>::for_size(1 << 4);
+        let points = (1..=5).map(Fp998244353::from).collect_vec();
+        let tree = SubproductTree::new(&points, &c);
+        assert_eq!(tree.root().coef.len(), 6);
+        points
+            .iter()
+            .for_each(|&x| assert_eq!(tree.root().evaluate(x), Fp998244353::from(0)));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let c = Constants::<Fp998244353>::for_size(1 << 12);
+        [1, 2, 7, 100, 300].iter().for_each(|&n| {
+            let points = (0..n).map(|x| Fp998244353::from(x * 7 + 3)).collect_vec();
+            let values = (0..n)
+                .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+                .collect_vec();
+            let f = interpolate(&points, &values, &c);
+            assert!(f.coef.len() <= n);
+            assert_eq!(evaluate_many(&f, &points, &c), values);
+        });
+    }
+
+    #[test]
+    fn test_interpolate_roots_of_unity() {
+        let n = 16;
+        let c = Constants::<Fp998244353>::for_size(n);
+        let mut points = vec![Fp998244353::from(1)];
+        (1..n).for_each(|i| points.push(points[i - 1] * c.w));
+        let values = (0..n)
+            .map(|_| Fp998244353::from(rand::thread_rng().gen::<u32>()))
+            .collect_vec();
+        let f = interpolate(&points, &values, &c);
+
+        // the same data out of order takes the subproduct-tree path
+        let g = interpolate(
+            &points.iter().rev().cloned().collect_vec(),
+            &values.iter().rev().cloned().collect_vec(),
+            &Constants::<Fp998244353>::for_size(1 << 6),
+        );
+        assert_eq!(f.coef, g.coef);
+    }
+}